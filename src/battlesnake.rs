@@ -0,0 +1,484 @@
+use std::{
+    sync::{mpsc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{Direction, Position},
+    controller::{BoardView, ControlInput, SnakeController},
+    grid::EdgePolicy,
+    simulation::Snake,
+};
+
+/// How long a move may take before the snake goes straight on. Ticks are short, so this is
+/// well under Battlesnake's own 500 ms.
+const DEFAULT_TIMEOUT: Duration = Duration::from_millis(100);
+/// There is no health in this game, so every snake reports full health.
+const FULL_HEALTH: u32 = 100;
+
+/// Body of a Battlesnake `/move` request.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveRequest {
+    pub game: Game,
+    pub turn: u64,
+    pub board: Board,
+    pub you: Battlesnake,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Game {
+    pub id: String,
+    pub ruleset: Ruleset,
+    pub map: String,
+    /// Milliseconds the snake has to answer.
+    pub timeout: u64,
+    pub source: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ruleset {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Board {
+    pub width: u16,
+    pub height: u16,
+    pub food: Vec<Coord>,
+    pub hazards: Vec<Coord>,
+    pub snakes: Vec<Battlesnake>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Battlesnake {
+    pub id: String,
+    pub name: String,
+    pub health: u32,
+    pub body: Vec<Coord>,
+    pub latency: String,
+    pub head: Coord,
+    pub length: usize,
+    pub shout: String,
+}
+
+/// Battlesnake coordinates share ours: origin at the bottom left, `y` growing upwards.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Coord {
+    pub x: i16,
+    pub y: i16,
+}
+
+impl From<&Position> for Coord {
+    fn from(position: &Position) -> Self {
+        Self {
+            x: position.x,
+            y: position.y,
+        }
+    }
+}
+
+/// Body of a `/move` response. Only the move matters here.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveResponse {
+    #[serde(rename = "move")]
+    pub direction: Move,
+    #[serde(default)]
+    pub shout: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Move {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl From<Move> for Direction {
+    fn from(direction: Move) -> Self {
+        match direction {
+            Move::Up => Self::Up,
+            Move::Down => Self::Down,
+            Move::Left => Self::Left,
+            Move::Right => Self::Right,
+        }
+    }
+}
+
+/// Dead snakes stay on the board and still kill here, unlike in Battlesnake. They are sent with
+/// their tail stacked, the way Battlesnake shows a snake that just ate, so servers don't expect
+/// the tail to move out of the way.
+fn battlesnake(snake: &Snake) -> Battlesnake {
+    let player_id = snake.player.id;
+    let mut body: Vec<Coord> = snake.body.iter().map(Coord::from).collect();
+    if !snake.is_alive() {
+        body.extend(body.last().copied());
+    }
+    Battlesnake {
+        id: format!("player-{player_id}"),
+        name: format!("Player {}", player_id + 1),
+        health: FULL_HEALTH,
+        length: body.len(),
+        body,
+        latency: "0".to_string(),
+        head: snake.head().into(),
+        shout: String::new(),
+    }
+}
+
+impl MoveRequest {
+    /// The board as `view`'s snake sees it.
+    #[must_use]
+    pub fn new(view: BoardView, game_id: &str, timeout: Duration) -> Self {
+        let board = view.board();
+        // Battlesnake's own name for a board whose edges wrap around.
+        let rules = match board.edges {
+            EdgePolicy::Walls => "standard",
+            EdgePolicy::Wrap => "wrapped",
+        };
+        Self {
+            game: Game {
+                id: game_id.to_string(),
+                ruleset: Ruleset {
+                    name: rules.to_string(),
+                    version: "v1.0.0".to_string(),
+                },
+                map: rules.to_string(),
+                timeout: u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX),
+                source: "bevy-snake".to_string(),
+            },
+            turn: view.tick(),
+            board: Board {
+                width: board.width,
+                height: board.height,
+                food: view.food().iter().map(Coord::from).collect(),
+                hazards: Vec::new(),
+                snakes: view.state().snakes.iter().map(battlesnake).collect(),
+            },
+            you: battlesnake(view.snake()),
+        }
+    }
+}
+
+type MoveResult = Result<Direction, Box<ureq::Error>>;
+
+/// Asks a Battlesnake server where to go every tick. Errors, bad answers and timeouts all
+/// keep the current direction.
+///
+/// In the game, requests go out on a background thread as soon as a new board is up, so a slow
+/// server never holds up the frame. Without per-frame input, e.g. headless, each tick waits
+/// for its answer instead.
+pub struct BattlesnakeController {
+    url: String,
+    /// Fresh for every game, so servers can tell rematches apart.
+    game_id: String,
+    timeout: Duration,
+    agent: ureq::Agent,
+    /// The turn being answered in the background, and where its answer will arrive. Only ever
+    /// used through `&mut self`, the mutex just makes the receiver `Sync`.
+    pending: Option<(u64, Mutex<mpsc::Receiver<MoveResult>>)>,
+}
+
+impl BattlesnakeController {
+    /// `url` is the snake's base URL; moves are posted to `{url}/move`.
+    #[must_use]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            game_id: new_game_id(),
+            timeout: DEFAULT_TIMEOUT,
+            agent: agent(DEFAULT_TIMEOUT),
+            pending: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self.agent = agent(timeout);
+        self
+    }
+
+    #[must_use]
+    pub fn game_id(&self) -> &str {
+        &self.game_id
+    }
+
+    fn move_url(&self) -> String {
+        format!("{}/move", self.url.trim_end_matches('/'))
+    }
+
+    /// Posts `request` and returns the answer, or the reason there was none.
+    pub fn request_move(&self, request: &MoveRequest) -> MoveResult {
+        post_move(&self.agent, &self.move_url(), request)
+    }
+
+    /// Posts `view`'s board on a background thread, unless that turn was already sent. The
+    /// next `next_direction` for the same turn uses the answer if it is back by then.
+    pub fn request_move_in_background(&mut self, view: BoardView) {
+        if matches!(self.pending, Some((turn, _)) if turn == view.tick()) {
+            return;
+        }
+        let request = MoveRequest::new(view, &self.game_id, self.timeout);
+        let (agent, url) = (self.agent.clone(), self.move_url());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(post_move(&agent, &url, &request));
+        });
+        self.pending = Some((view.tick(), Mutex::new(receiver)));
+    }
+}
+
+fn post_move(agent: &ureq::Agent, url: &str, request: &MoveRequest) -> MoveResult {
+    let response = agent.post(url).send_json(request).map_err(Box::new)?;
+    let response: MoveResponse = response
+        .into_json()
+        .map_err(|error| Box::new(error.into()))?;
+    Ok(response.direction.into())
+}
+
+fn agent(timeout: Duration) -> ureq::Agent {
+    ureq::AgentBuilder::new().timeout(timeout).build()
+}
+
+fn new_game_id() -> String {
+    format!("bevy-snake-{:016x}", rand::random::<u64>())
+}
+
+impl SnakeController for BattlesnakeController {
+    fn next_direction(&mut self, view: BoardView) -> Option<Direction> {
+        let result = match self.pending.take() {
+            Some((turn, receiver)) if turn == view.tick() => {
+                let Ok(result) = receiver
+                    .into_inner()
+                    .map_err(drop)
+                    .and_then(|receiver| receiver.try_recv().map_err(drop))
+                else {
+                    eprintln!("{}: no move in time", self.url);
+                    return None;
+                };
+                result
+            }
+            _ => self.request_move(&MoveRequest::new(view, &self.game_id, self.timeout)),
+        };
+        match result {
+            Ok(direction) => Some(direction),
+            Err(error) => {
+                eprintln!("{}: {error}", self.url);
+                None
+            }
+        }
+    }
+
+    fn read_input(&mut self, view: BoardView, _input: &mut ControlInput) {
+        self.request_move_in_background(view);
+    }
+
+    fn restart(&mut self) {
+        self.game_id = new_game_id();
+        self.pending = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::mpsc,
+        thread,
+    };
+
+    use super::*;
+    use crate::{grid::BoardConfig, simulation::GameState};
+
+    /// Answers `count` requests with `reply` after `delay`, passing each request body back.
+    fn stub_server(
+        count: usize,
+        reply: &'static str,
+        delay: Duration,
+    ) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().take(count) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                sender.send(String::from_utf8(body).unwrap()).unwrap();
+
+                thread::sleep(delay);
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{reply}",
+                    reply.len()
+                );
+            }
+        });
+        (url, receiver)
+    }
+
+    #[test]
+    fn request_follows_the_battlesnake_format() {
+        let mut state = GameState::new(BoardConfig::new(11, 11), 2);
+        state.food = vec![Position { x: 5, y: 5 }];
+        state.tick = 7;
+
+        let request = MoveRequest::new(BoardView::new(&state, 1), "test", DEFAULT_TIMEOUT);
+        let json: serde_json::Value = serde_json::to_value(&request).unwrap();
+
+        assert_eq!(json["turn"], 7);
+        assert_eq!(json["game"]["timeout"], 100);
+        assert_eq!(json["board"]["width"], 11);
+        assert_eq!(
+            json["board"]["food"],
+            serde_json::json!([{ "x": 5, "y": 5 }])
+        );
+        assert_eq!(json["board"]["snakes"].as_array().unwrap().len(), 2);
+        assert_eq!(json["you"]["id"], "player-1");
+        assert_eq!(json["you"]["head"], json["you"]["body"][0]);
+        assert_eq!(json["you"]["length"], 2);
+    }
+
+    #[test]
+    fn wrapping_boards_use_the_wrapped_rules() {
+        let walls = GameState::default();
+        let wrap = GameState::new(BoardConfig::default().with_edges(EdgePolicy::Wrap), 2);
+
+        let walls = MoveRequest::new(BoardView::new(&walls, 0), "test", DEFAULT_TIMEOUT);
+        let wrap = MoveRequest::new(BoardView::new(&wrap, 0), "test", DEFAULT_TIMEOUT);
+
+        assert_eq!(
+            (walls.game.ruleset.name.as_str(), walls.game.map.as_str()),
+            ("standard", "standard")
+        );
+        assert_eq!(
+            (wrap.game.ruleset.name.as_str(), wrap.game.map.as_str()),
+            ("wrapped", "wrapped")
+        );
+    }
+
+    #[test]
+    fn dead_snakes_stay_on_the_board_without_moving() {
+        let mut state = GameState::default();
+        state.snakes[1].collision = Some(crate::components::Collision::Wall);
+
+        let request = MoveRequest::new(BoardView::new(&state, 0), "test", DEFAULT_TIMEOUT);
+
+        assert_eq!(request.board.snakes.len(), 2);
+        let dead = &request.board.snakes[1];
+        let tail = Coord::from(state.snakes[1].body.back().unwrap());
+        assert_eq!(dead.body[dead.body.len() - 2..], [tail, tail]);
+        assert_eq!(dead.length, state.snakes[1].body.len() + 1);
+        assert_eq!(
+            request.board.snakes[0].body.len(),
+            state.snakes[0].body.len()
+        );
+    }
+
+    #[test]
+    fn controller_applies_the_returned_move() {
+        let (url, requests) = stub_server(1, r#"{"move": "left", "shout": "hi"}"#, Duration::ZERO);
+        let state = GameState::default();
+        let mut controller = BattlesnakeController::new(url).with_timeout(Duration::from_secs(5));
+
+        let direction = controller.next_direction(BoardView::new(&state, 0));
+
+        assert_eq!(direction, Some(Direction::Left));
+        let request: MoveRequest = serde_json::from_str(&requests.recv().unwrap()).unwrap();
+        assert_eq!(
+            request,
+            MoveRequest::new(
+                BoardView::new(&state, 0),
+                controller.game_id(),
+                Duration::from_secs(5)
+            )
+        );
+    }
+
+    #[test]
+    fn background_answers_steer_the_turn_they_were_asked_for() {
+        let (url, requests) = stub_server(1, r#"{"move": "right"}"#, Duration::ZERO);
+        let state = GameState::default();
+        let mut controller = BattlesnakeController::new(url).with_timeout(Duration::from_secs(5));
+
+        controller.request_move_in_background(BoardView::new(&state, 0));
+        requests.recv().unwrap();
+        thread::sleep(Duration::from_millis(200));
+
+        assert_eq!(
+            controller.next_direction(BoardView::new(&state, 0)),
+            Some(Direction::Right)
+        );
+    }
+
+    #[test]
+    fn slow_background_answers_do_not_hold_up_the_tick() {
+        let (url, _requests) = stub_server(1, r#"{"move": "left"}"#, Duration::from_millis(500));
+        let state = GameState::default();
+        let mut controller = BattlesnakeController::new(url).with_timeout(Duration::from_secs(5));
+
+        controller.request_move_in_background(BoardView::new(&state, 0));
+        let started = std::time::Instant::now();
+        let direction = controller.next_direction(BoardView::new(&state, 0));
+
+        assert_eq!(direction, None);
+        assert!(started.elapsed() < Duration::from_millis(100));
+    }
+
+    #[test]
+    fn every_game_gets_its_own_id() {
+        let mut controller = BattlesnakeController::new("http://127.0.0.1:1");
+        let first = controller.game_id().to_string();
+
+        controller.restart();
+
+        assert_ne!(controller.game_id(), first);
+    }
+
+    #[test]
+    fn slow_servers_keep_the_current_direction() {
+        let (url, _requests) = stub_server(1, r#"{"move": "left"}"#, Duration::from_millis(500));
+        let state = GameState::default();
+        let mut controller =
+            BattlesnakeController::new(url).with_timeout(Duration::from_millis(50));
+
+        assert_eq!(controller.next_direction(BoardView::new(&state, 0)), None);
+    }
+
+    #[test]
+    fn unreachable_servers_keep_the_current_direction() {
+        // Nothing listens on a port that was just released.
+        let url = format!(
+            "http://{}",
+            TcpListener::bind("127.0.0.1:0")
+                .unwrap()
+                .local_addr()
+                .unwrap()
+        );
+        let state = GameState::default();
+        let mut controller = BattlesnakeController::new(url);
+
+        assert_eq!(controller.next_direction(BoardView::new(&state, 0)), None);
+    }
+}
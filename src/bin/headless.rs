@@ -0,0 +1,63 @@
+//! Plays bot-vs-bot matches without a window, as fast as possible, and prints the results.
+//!
+//! `headless --controllers lookahead,greedy --matches 500` pits a look-ahead bot against a
+//! greedy one. Controllers are bot levels, Battlesnake URLs or `none` to go straight.
+
+use bevy::prelude::*;
+use bevy_snake::{
+    battlesnake::BattlesnakeController,
+    bot::{BotController, BotLevel},
+    cli::Args,
+    controller::Controllers,
+    headless::{self, MatchLimits, MatchStats},
+    simulation::GameState,
+};
+
+fn main() {
+    let args = Args::from_env();
+    let board = args.board();
+    let rng = args.rng();
+    println!("Seed: {}", rng.seed());
+    let food_config = args.food();
+    let limits = MatchLimits {
+        matches: args
+            .parsed("--matches")
+            .unwrap_or_else(|| MatchLimits::default().matches),
+        max_ticks: args
+            .parsed("--max-ticks")
+            .unwrap_or_else(|| MatchLimits::default().max_ticks),
+    };
+
+    let names = args
+        .value("--controllers")
+        .unwrap_or_else(|| "safe,safe".to_string());
+    let mut controllers = Controllers::default();
+    let mut players = 0;
+    for name in names.split(',') {
+        if name.starts_with("http://") || name.starts_with("https://") {
+            controllers.set(players, BattlesnakeController::new(name));
+        } else if let Some(level) = BotLevel::from_name(name) {
+            controllers.set(players, BotController(level));
+        } else if name != "none" {
+            eprintln!("Unknown controller {name}, expected a bot level, a URL or none");
+            std::process::exit(1);
+        }
+        players += 1;
+    }
+
+    let state = GameState::new(board, players).with_food(food_config);
+    if let Err(error) = state.check_spawns() {
+        eprintln!("--controllers: {error}");
+        std::process::exit(1);
+    }
+
+    App::new()
+        .add_plugins(MinimalPlugins)
+        .insert_resource(state)
+        .insert_resource(rng)
+        .insert_resource(controllers)
+        .insert_resource(limits)
+        .insert_resource(MatchStats::new(players))
+        .add_system(headless::headless_match_system)
+        .run();
+}
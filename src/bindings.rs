@@ -0,0 +1,367 @@
+use std::{collections::HashMap, fmt, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::Direction,
+    controller::{BoardView, ControlInput, Controllers, SnakeController},
+    snake::TurnQueue,
+    tick::TickBoost,
+};
+
+const DEFAULT_PLAYER_BINDINGS: [PlayerBindings; 4] = [
+    PlayerBindings::new(KeyCode::W, KeyCode::S, KeyCode::A, KeyCode::D).with_boost(KeyCode::LShift),
+    PlayerBindings::new(KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right)
+        .with_boost(KeyCode::RShift),
+    PlayerBindings::new(KeyCode::I, KeyCode::K, KeyCode::J, KeyCode::L),
+    PlayerBindings::new(
+        KeyCode::Numpad8,
+        KeyCode::Numpad5,
+        KeyCode::Numpad4,
+        KeyCode::Numpad6,
+    ),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    TurnUp,
+    TurnDown,
+    TurnLeft,
+    TurnRight,
+    Boost,
+    Pause,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerBindings {
+    pub up: KeyCode,
+    pub down: KeyCode,
+    pub left: KeyCode,
+    pub right: KeyCode,
+    #[serde(default)]
+    pub boost: Option<KeyCode>,
+}
+
+impl PlayerBindings {
+    #[must_use]
+    pub const fn new(up: KeyCode, down: KeyCode, left: KeyCode, right: KeyCode) -> Self {
+        Self {
+            up,
+            down,
+            left,
+            right,
+            boost: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_boost(mut self, boost: KeyCode) -> Self {
+        self.boost = Some(boost);
+        self
+    }
+
+    fn keys(&self) -> impl Iterator<Item = (Action, KeyCode)> {
+        [
+            (Action::TurnUp, Some(self.up)),
+            (Action::TurnDown, Some(self.down)),
+            (Action::TurnLeft, Some(self.left)),
+            (Action::TurnRight, Some(self.right)),
+            (Action::Boost, self.boost),
+        ]
+        .into_iter()
+        .filter_map(|(action, key)| Some((action, key?)))
+    }
+
+    /// Held keys keep offering their turn; `TurnQueue` drops the repeats.
+    #[must_use]
+    pub fn pressed(&self, keyboard_input: &Input<KeyCode>) -> Option<Direction> {
+        if keyboard_input.pressed(self.left) {
+            Some(Direction::Left)
+        } else if keyboard_input.pressed(self.down) {
+            Some(Direction::Down)
+        } else if keyboard_input.pressed(self.up) {
+            Some(Direction::Up)
+        } else if keyboard_input.pressed(self.right) {
+            Some(Direction::Right)
+        } else {
+            None
+        }
+    }
+}
+
+/// Keyboard controls: one entry per keyboard player, indexed by `Player::id`, plus shared pause keys.
+#[derive(Resource, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputBindings {
+    pub players: Vec<PlayerBindings>,
+    pub pause: Vec<KeyCode>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self::new(2)
+    }
+}
+
+/// Who a key is bound to; `player_id` is `None` for shared actions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoundAction {
+    pub player_id: Option<u8>,
+    pub action: Action,
+}
+
+impl fmt::Display for BoundAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.player_id {
+            Some(player_id) => write!(f, "player {} {:?}", player_id + 1, self.action),
+            None => write!(f, "{:?}", self.action),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum BindingsError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Conflict {
+        key: KeyCode,
+        first: BoundAction,
+        second: BoundAction,
+    },
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "could not read bindings: {error}"),
+            Self::Parse(error) => write!(f, "invalid bindings: {error}"),
+            Self::Conflict { key, first, second } => {
+                write!(f, "{key:?} is bound to both {first} and {second}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BindingsError {}
+
+impl InputBindings {
+    /// Default keys for the first four players; later players have no keyboard controls.
+    #[must_use]
+    pub fn new(players: u8) -> Self {
+        Self {
+            players: DEFAULT_PLAYER_BINDINGS
+                .into_iter()
+                .take(usize::from(players))
+                .collect(),
+            pause: vec![KeyCode::P, KeyCode::Escape],
+        }
+    }
+
+    pub fn from_ron(source: &str) -> Result<Self, BindingsError> {
+        let bindings: Self = ron::from_str(source).map_err(BindingsError::Parse)?;
+        bindings.validate()?;
+        Ok(bindings)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BindingsError> {
+        Self::from_ron(&std::fs::read_to_string(path).map_err(BindingsError::Io)?)
+    }
+
+    /// Fails on the first key bound to more than one action.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn validate(&self) -> Result<(), BindingsError> {
+        let player_keys = self
+            .players
+            .iter()
+            .enumerate()
+            .flat_map(|(player_id, keys)| {
+                keys.keys().map(move |(action, key)| {
+                    let player_id = Some(player_id as u8);
+                    (key, BoundAction { player_id, action })
+                })
+            });
+        let pause_keys = self.pause.iter().map(|key| {
+            let action = Action::Pause;
+            (
+                *key,
+                BoundAction {
+                    player_id: None,
+                    action,
+                },
+            )
+        });
+
+        let mut bound = HashMap::new();
+        for (key, action) in player_keys.chain(pause_keys) {
+            if let Some(first) = bound.insert(key, action) {
+                return Err(BindingsError::Conflict {
+                    key,
+                    first,
+                    second: action,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// A keyboard controller for every player with keys.
+    #[allow(clippy::cast_possible_truncation)]
+    #[must_use]
+    pub fn controllers(&self) -> Controllers {
+        self.players.iter().enumerate().fold(
+            Controllers::default(),
+            |controllers, (player_id, keys)| {
+                controllers.with(player_id as u8, KeyboardController::new(*keys))
+            },
+        )
+    }
+
+    #[must_use]
+    pub fn pause_just_pressed(&self, keyboard_input: &Input<KeyCode>) -> bool {
+        keyboard_input.any_just_pressed(self.pause.iter().copied())
+    }
+
+    #[must_use]
+    pub fn boost_pressed(&self, keyboard_input: &Input<KeyCode>) -> bool {
+        keyboard_input.any_pressed(self.players.iter().filter_map(|keys| keys.boost))
+    }
+}
+
+/// Steers with one player's keys, buffering turns pressed between ticks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyboardController {
+    keys: PlayerBindings,
+    turns: TurnQueue,
+}
+
+impl KeyboardController {
+    #[must_use]
+    pub fn new(keys: PlayerBindings) -> Self {
+        Self {
+            keys,
+            turns: TurnQueue::default(),
+        }
+    }
+}
+
+impl SnakeController for KeyboardController {
+    fn next_direction(&mut self, _view: BoardView) -> Option<Direction> {
+        self.turns.pop()
+    }
+
+    fn read_input(&mut self, view: BoardView, input: &mut ControlInput) {
+        if let Some(turn) = input
+            .keyboard
+            .as_ref()
+            .and_then(|keyboard| self.keys.pressed(keyboard))
+        {
+            self.turns.push(view.snake().direction, turn);
+        }
+    }
+
+    fn restart(&mut self) {
+        self.turns.clear();
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn boost_input_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<InputBindings>,
+    mut boost: ResMut<TickBoost>,
+) {
+    boost.0 = bindings.boost_pressed(&keyboard_input);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_bindings_have_no_conflicts() {
+        assert!(InputBindings::new(4).validate().is_ok());
+        assert_eq!(InputBindings::new(6).players.len(), 4);
+    }
+
+    #[test]
+    fn bindings_load_from_ron() {
+        let bindings = InputBindings::from_ron(
+            "(
+                players: [
+                    (up: W, down: S, left: A, right: D, boost: Some(Space)),
+                    (up: Up, down: Down, left: Left, right: Right),
+                ],
+                pause: [P],
+            )",
+        )
+        .unwrap();
+
+        assert_eq!(bindings.players[0].boost, Some(KeyCode::Space));
+        assert_eq!(bindings.players[1].boost, None);
+        assert_eq!(bindings.pause, vec![KeyCode::P]);
+    }
+
+    #[test]
+    fn bundled_bindings_file_is_valid() {
+        let bindings = InputBindings::load("assets/bindings.ron").unwrap();
+
+        assert_eq!(bindings, InputBindings::new(4));
+    }
+
+    #[test]
+    fn conflicting_bindings_are_rejected() {
+        let error = InputBindings::from_ron(
+            "(
+                players: [
+                    (up: W, down: S, left: A, right: D),
+                    (up: Up, down: Down, left: A, right: Right),
+                ],
+                pause: [P],
+            )",
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            error,
+            BindingsError::Conflict {
+                key: KeyCode::A,
+                first: BoundAction {
+                    player_id: Some(0),
+                    action: Action::TurnLeft
+                },
+                second: BoundAction {
+                    player_id: Some(1),
+                    action: Action::TurnLeft
+                },
+            }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "A is bound to both player 1 TurnLeft and player 2 TurnLeft"
+        );
+    }
+
+    #[test]
+    fn pause_keys_cannot_steer() {
+        let mut bindings = InputBindings::default();
+        bindings.pause.push(KeyCode::Up);
+
+        assert!(matches!(
+            bindings.validate(),
+            Err(BindingsError::Conflict {
+                key: KeyCode::Up,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn boost_is_held_by_any_player() {
+        let bindings = InputBindings::default();
+        let mut input = Input::<KeyCode>::default();
+
+        assert!(!bindings.boost_pressed(&input));
+        input.press(KeyCode::RShift);
+        assert!(bindings.boost_pressed(&input));
+    }
+}
@@ -0,0 +1,357 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::{
+    components::{Direction, GameEndEvent, Player, Position},
+    controller::{BoardView, SnakeController},
+    simulation::{next_position, GameState},
+};
+
+const LOOK_AHEAD_DEPTH: u8 = 3;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BotLevel {
+    /// Shortest path to the nearest food, only avoiding the next cell's obstacles.
+    Greedy,
+    /// Follows the shortest path unless it leads into a space too small to fit the snake.
+    #[default]
+    Safe,
+    /// Plays out the next few ticks with the real rules and picks the best outcome.
+    LookAhead,
+}
+
+impl BotLevel {
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "greedy" => Some(Self::Greedy),
+            "safe" => Some(Self::Safe),
+            "lookahead" => Some(Self::LookAhead),
+            _ => None,
+        }
+    }
+}
+
+/// Plugs the built-in bots into `Controllers`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BotController(pub BotLevel);
+
+impl SnakeController for BotController {
+    fn next_direction(&mut self, view: BoardView) -> Option<Direction> {
+        choose_direction(view.state(), view.player_id(), self.0)
+    }
+}
+
+/// Next direction for `player_id`, or `None` if that snake is dead or missing.
+#[must_use]
+pub fn choose_direction(state: &GameState, player_id: u8, level: BotLevel) -> Option<Direction> {
+    let snake = state.snakes.get(usize::from(player_id))?;
+    if !snake.is_alive() {
+        return None;
+    }
+    let direction = match level {
+        BotLevel::Greedy => greedy(state, player_id),
+        BotLevel::Safe => safe(state, player_id),
+        BotLevel::LookAhead => look_ahead(state, player_id),
+    };
+    Some(direction.unwrap_or(snake.direction))
+}
+
+fn blocked_cells(state: &GameState) -> HashSet<Position> {
+    state
+        .snakes
+        .iter()
+        .flat_map(|snake| snake.body.iter().cloned())
+        .collect()
+}
+
+fn neighbour(state: &GameState, position: &Position, direction: Direction) -> Option<Position> {
+    let next = state.board.wrap(next_position(position, direction));
+    state.board.contains(&next).then_some(next)
+}
+
+/// Directions the snake can take without hitting a wall or a body on the next tick.
+fn open_moves(state: &GameState, player_id: u8, blocked: &HashSet<Position>) -> Vec<Direction> {
+    let snake = &state.snakes[usize::from(player_id)];
+    Direction::ALL
+        .into_iter()
+        .filter(|direction| *direction != snake.direction.opposite())
+        .filter(|direction| {
+            neighbour(state, snake.head(), *direction).is_some_and(|next| !blocked.contains(&next))
+        })
+        .collect()
+}
+
+/// First step of the shortest path from the head to any food.
+fn path_to_food(
+    state: &GameState,
+    player_id: u8,
+    blocked: &HashSet<Position>,
+) -> Option<Direction> {
+    let head = state.snakes[usize::from(player_id)].head();
+    let mut visited = HashSet::from([head.clone()]);
+    let mut queue: VecDeque<(Position, Direction)> = open_moves(state, player_id, blocked)
+        .into_iter()
+        .filter_map(|direction| Some((neighbour(state, head, direction)?, direction)))
+        .collect();
+    while let Some((position, first_step)) = queue.pop_front() {
+        if !visited.insert(position.clone()) {
+            continue;
+        }
+        if state.food.contains(&position) {
+            return Some(first_step);
+        }
+        for direction in Direction::ALL {
+            if let Some(next) = neighbour(state, &position, direction) {
+                if !blocked.contains(&next) && !visited.contains(&next) {
+                    queue.push_back((next, first_step));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Number of free cells reachable from `start`, counting at most `limit`.
+fn reachable_area(
+    state: &GameState,
+    start: &Position,
+    blocked: &HashSet<Position>,
+    limit: usize,
+) -> usize {
+    let mut visited = HashSet::from([start.clone()]);
+    let mut queue = VecDeque::from([start.clone()]);
+    while let Some(position) = queue.pop_front() {
+        if visited.len() >= limit {
+            break;
+        }
+        for direction in Direction::ALL {
+            if let Some(next) = neighbour(state, &position, direction) {
+                if !blocked.contains(&next) && visited.insert(next.clone()) {
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    visited.len()
+}
+
+fn greedy(state: &GameState, player_id: u8) -> Option<Direction> {
+    let blocked = blocked_cells(state);
+    path_to_food(state, player_id, &blocked)
+        .or_else(|| open_moves(state, player_id, &blocked).first().copied())
+}
+
+fn safe(state: &GameState, player_id: u8) -> Option<Direction> {
+    let blocked = blocked_cells(state);
+    let snake = &state.snakes[usize::from(player_id)];
+    let needed = snake.body.len() + 1;
+    let mut moves = open_moves(state, player_id, &blocked);
+    if let Some(towards_food) = path_to_food(state, player_id, &blocked) {
+        moves.retain(|direction| *direction != towards_food);
+        moves.insert(0, towards_food);
+    }
+
+    let area = |direction: Direction| {
+        neighbour(state, snake.head(), direction)
+            .map_or(0, |next| reachable_area(state, &next, &blocked, needed))
+    };
+    moves
+        .iter()
+        .copied()
+        .find(|direction| area(*direction) >= needed)
+        .or_else(|| {
+            moves
+                .iter()
+                .copied()
+                .max_by_key(|direction| area(*direction))
+        })
+}
+
+fn look_ahead(state: &GameState, player_id: u8) -> Option<Direction> {
+    candidate_moves(state, player_id)
+        .map(|direction| {
+            let score = score_after(state, player_id, direction, LOOK_AHEAD_DEPTH - 1);
+            (score, direction)
+        })
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, direction)| direction)
+}
+
+fn candidate_moves(state: &GameState, player_id: u8) -> impl Iterator<Item = Direction> {
+    let current = state.snakes[usize::from(player_id)].direction;
+    Direction::ALL
+        .into_iter()
+        .filter(move |direction| *direction != current.opposite())
+}
+
+/// Steps a copy of the game with `direction` for this snake and the others going straight.
+fn score_after(state: &GameState, player_id: u8, direction: Direction, depth: u8) -> i64 {
+    let mut next = state.clone();
+    let mut inputs = vec![None; next.snakes.len()];
+    inputs[usize::from(player_id)] = Some(direction);
+    next.step(&inputs);
+
+    let snake = &next.snakes[usize::from(player_id)];
+    if !snake.is_alive() {
+        return i64::MIN / 2;
+    }
+    if let Some(GameEndEvent::Winner(Player { id })) = &next.outcome {
+        if *id == player_id {
+            return i64::MAX / 2;
+        }
+    }
+    if depth == 0 {
+        return leaf_score(&next, player_id);
+    }
+    candidate_moves(&next, player_id)
+        .map(|direction| score_after(&next, player_id, direction, depth - 1))
+        .max()
+        .unwrap_or(i64::MIN / 2)
+}
+
+/// Prefers room to move, then length, then being close to food.
+#[allow(clippy::cast_possible_wrap)]
+fn leaf_score(state: &GameState, player_id: u8) -> i64 {
+    let snake = &state.snakes[usize::from(player_id)];
+    let length = snake.body.len();
+    let blocked = blocked_cells(state);
+    let area = reachable_area(state, snake.head(), &blocked, length * 2);
+    let food_distance = state
+        .food
+        .iter()
+        .map(|food| {
+            i64::from((food.x - snake.head().x).abs()) + i64::from((food.y - snake.head().y).abs())
+        })
+        .min()
+        .unwrap_or(0);
+    let room = if area > length { 0 } else { -10_000 };
+    room + area as i64 * 10 + length as i64 * 100 - food_distance
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        grid::BoardConfig,
+        simulation::{Snake, SpawnPoint},
+    };
+
+    fn snake(id: u8, direction: Direction, body: &[(i16, i16)]) -> Snake {
+        let mut snake = Snake::spawn(
+            id,
+            SpawnPoint {
+                head: Position {
+                    x: body[0].0,
+                    y: body[0].1,
+                },
+                direction,
+            },
+        );
+        snake.body = body.iter().map(|&(x, y)| Position { x, y }).collect();
+        snake
+    }
+
+    /// Food at (7, 5) sits in a one-cell pocket walled in by player 1.
+    fn pocket() -> GameState {
+        let mut state = GameState::with_spawn_points(BoardConfig::new(10, 10), []);
+        state.snakes = vec![
+            snake(
+                0,
+                Direction::Right,
+                &[(6, 5), (5, 5), (4, 5), (3, 5), (2, 5)],
+            ),
+            snake(
+                1,
+                Direction::Left,
+                &[
+                    (4, 8),
+                    (5, 8),
+                    (6, 8),
+                    (7, 8),
+                    (7, 7),
+                    (7, 6),
+                    (8, 6),
+                    (8, 5),
+                    (8, 4),
+                    (7, 4),
+                    (7, 3),
+                    (7, 2),
+                    (6, 2),
+                    (5, 2),
+                    (4, 2),
+                ],
+            ),
+        ];
+        state.food = vec![Position { x: 7, y: 5 }];
+        state
+    }
+
+    #[test]
+    fn greedy_bot_takes_the_shortest_path_to_food() {
+        let mut state = GameState::new(BoardConfig::default(), 1);
+        state.food = vec![Position { x: 8, y: 3 }];
+
+        assert_eq!(
+            choose_direction(&state, 0, BotLevel::Greedy),
+            Some(Direction::Right)
+        );
+    }
+
+    #[test]
+    fn bots_turn_away_from_walls() {
+        let mut state = GameState::with_spawn_points(BoardConfig::new(10, 10), []);
+        state.snakes = vec![snake(0, Direction::Up, &[(5, 9), (5, 8)])];
+
+        for level in [BotLevel::Greedy, BotLevel::Safe, BotLevel::LookAhead] {
+            let direction = choose_direction(&state, 0, level);
+            assert!(
+                matches!(direction, Some(Direction::Left | Direction::Right)),
+                "{level:?} chose {direction:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn greedy_bot_walks_into_a_dead_end_for_food() {
+        assert_eq!(
+            choose_direction(&pocket(), 0, BotLevel::Greedy),
+            Some(Direction::Right)
+        );
+    }
+
+    #[test]
+    fn careful_bots_avoid_the_dead_end() {
+        for level in [BotLevel::Safe, BotLevel::LookAhead] {
+            let direction = choose_direction(&pocket(), 0, level);
+            assert!(
+                matches!(direction, Some(Direction::Up | Direction::Down)),
+                "{level:?} chose {direction:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn dead_snakes_have_no_move() {
+        let mut state = GameState::default();
+        state.snakes[0].collision = Some(crate::components::Collision::Wall);
+
+        assert_eq!(choose_direction(&state, 0, BotLevel::Safe), None);
+        assert_eq!(choose_direction(&state, 5, BotLevel::Safe), None);
+    }
+
+    #[test]
+    fn bot_survives_a_solo_game() {
+        let mut state = GameState::new(BoardConfig::new(12, 12), 1);
+        let mut rng = crate::rng::GameRng::new(3);
+        state.refill_food(&mut rng);
+
+        for _ in 0..200 {
+            let direction = choose_direction(&state, 0, BotLevel::LookAhead);
+            state.step(&[direction]);
+            state.spawn_due_food(&mut rng);
+        }
+
+        assert!(state.snakes[0].is_alive());
+        assert!(state.food_eaten > 0);
+    }
+}
@@ -0,0 +1,135 @@
+use std::str::FromStr;
+
+use crate::{
+    food::{FoodConfig, FoodSpawn},
+    grid::{BoardConfig, EdgePolicy},
+    rng::GameRng,
+};
+
+/// Command-line flags shared by the game and the headless runner.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Args(Vec<String>);
+
+impl Args {
+    #[must_use]
+    pub fn from_env() -> Self {
+        Self(std::env::args().collect())
+    }
+
+    #[must_use]
+    pub fn flag(&self, name: &str) -> bool {
+        self.0.iter().any(|arg| arg == name)
+    }
+
+    /// The argument after `name`, if there is one.
+    #[must_use]
+    pub fn value(&self, name: &str) -> Option<String> {
+        let mut args = self.0.iter().skip_while(|arg| *arg != name);
+        args.next()?;
+        args.next().cloned()
+    }
+
+    /// The argument after `name`, if there is one and it parses.
+    #[must_use]
+    pub fn parsed<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.value(name)?.parse().ok()
+    }
+
+    /// `--wrap` for a board without walls.
+    #[must_use]
+    pub fn board(&self) -> BoardConfig {
+        let edges = if self.flag("--wrap") {
+            EdgePolicy::Wrap
+        } else {
+            EdgePolicy::Walls
+        };
+        BoardConfig::default().with_edges(edges)
+    }
+
+    /// Whether `--seed` or `--daily` fixes the game's randomness.
+    #[must_use]
+    pub fn seeded(&self) -> bool {
+        self.flag("--daily") || self.value("--seed").is_some()
+    }
+
+    /// `--daily`, then `--seed`, then a fresh random seed.
+    #[must_use]
+    pub fn rng(&self) -> GameRng {
+        if self.flag("--daily") {
+            GameRng::daily()
+        } else {
+            self.parsed("--seed")
+                .map_or_else(GameRng::from_entropy, GameRng::new)
+        }
+    }
+
+    /// `--max-food` and `--food-on-eat` over the default food rules.
+    #[must_use]
+    pub fn food(&self) -> FoodConfig {
+        let default = FoodConfig::default();
+        FoodConfig {
+            max_food: self.parsed("--max-food").unwrap_or(default.max_food),
+            spawn: if self.flag("--food-on-eat") {
+                FoodSpawn::OnEat
+            } else {
+                default.spawn
+            },
+        }
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for Args {
+    fn from_iter<I: IntoIterator<Item = S>>(args: I) -> Self {
+        Self(args.into_iter().map(Into::into).collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn values_follow_their_flag() {
+        let args: Args = ["snake", "--seed", "42", "--players"].into_iter().collect();
+
+        assert_eq!(args.value("--seed"), Some("42".to_string()));
+        assert_eq!(args.parsed::<u64>("--seed"), Some(42));
+        assert_eq!(args.value("--players"), None);
+        assert_eq!(args.value("--speed"), None);
+    }
+
+    #[test]
+    fn game_options_default_without_flags() {
+        let args: Args = std::iter::once("snake").collect();
+
+        assert_eq!(args.board(), BoardConfig::default());
+        assert_eq!(args.food(), FoodConfig::default());
+        assert!(!args.seeded());
+    }
+
+    #[test]
+    fn game_options_come_from_flags() {
+        let args: Args = [
+            "snake",
+            "--wrap",
+            "--seed",
+            "7",
+            "--max-food",
+            "5",
+            "--food-on-eat",
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(args.board().edges, EdgePolicy::Wrap);
+        assert_eq!(args.rng(), GameRng::new(7));
+        assert!(args.seeded());
+        assert_eq!(
+            args.food(),
+            FoodConfig {
+                max_food: 5,
+                spawn: FoodSpawn::OnEat,
+            }
+        );
+    }
+}
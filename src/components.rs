@@ -1,14 +1,15 @@
 use std::fmt::{self, Display};
 
 use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
 
-#[derive(Component, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Component, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Position {
     pub x: i16,
     pub y: i16,
 }
 
-#[derive(Component, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Component, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Player {
     pub id: u8,
 }
@@ -35,7 +36,7 @@ impl Size {
     }
 }
 
-#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum Direction {
     Left,
     Up,
@@ -44,6 +45,8 @@ pub enum Direction {
 }
 
 impl Direction {
+    pub const ALL: [Self; 4] = [Self::Up, Self::Down, Self::Left, Self::Right];
+
     #[must_use]
     pub const fn opposite(self) -> Self {
         match self {
@@ -55,21 +58,28 @@ impl Direction {
     }
 }
 
-#[derive(Component, Clone, Debug, PartialEq, Eq)]
-pub enum GameEndEvent {
-    GameOver,
+/// What a snake's head ran into when it died.
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone, Serialize, Deserialize)]
+pub enum Collision {
+    Wall,
+    Itself,
+    Opponent(u8),
 }
 
-impl Default for GameEndEvent {
-    fn default() -> Self {
-        Self::GameOver
-    }
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GameEndEvent {
+    Winner(Player),
+    Draw,
+    #[default]
+    AllDead,
 }
 
 impl Display for GameEndEvent {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::GameOver => write!(f, "Game Over!"),
+            Self::Winner(player) => write!(f, "Player {} wins!", player.id() + 1),
+            Self::Draw => write!(f, "Draw!"),
+            Self::AllDead => write!(f, "Game Over!"),
         }
     }
 }
@@ -81,10 +91,10 @@ mod test {
     #[test]
     fn sized_square_is_created_calling_square_fn() {
         let expected = Size {
-            width: 3.14,
-            height: 3.14,
+            width: 2.5,
+            height: 2.5,
         };
-        let actual = Size::square(3.14);
+        let actual = Size::square(2.5);
 
         assert_eq!(actual, expected);
     }
@@ -96,4 +106,14 @@ mod test {
         assert_eq!(Direction::Right.opposite(), Direction::Left);
         assert_eq!(Direction::Left.opposite(), Direction::Right);
     }
+
+    #[test]
+    fn game_end_event_display() {
+        assert_eq!(
+            GameEndEvent::Winner(Player { id: 1 }).to_string(),
+            "Player 2 wins!"
+        );
+        assert_eq!(GameEndEvent::Draw.to_string(), "Draw!");
+        assert_eq!(GameEndEvent::AllDead.to_string(), "Game Over!");
+    }
 }
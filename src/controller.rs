@@ -0,0 +1,352 @@
+use std::collections::VecDeque;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    components::{Direction, Player, Position},
+    gamepad::{GamepadAssignments, StickDeadzone},
+    grid::BoardConfig,
+    net::Lockstep,
+    simulation::{GameState, Snake},
+    snake::Head,
+    tick::TickTimer,
+};
+
+/// What a controller gets to see of the game: everything, but read-only.
+#[derive(Clone, Copy)]
+pub struct BoardView<'a> {
+    state: &'a GameState,
+    player_id: u8,
+}
+
+impl<'a> BoardView<'a> {
+    #[must_use]
+    pub const fn new(state: &'a GameState, player_id: u8) -> Self {
+        Self { state, player_id }
+    }
+
+    #[must_use]
+    pub const fn player_id(&self) -> u8 {
+        self.player_id
+    }
+
+    /// The controlled snake.
+    #[must_use]
+    pub fn snake(&self) -> &'a Snake {
+        &self.state.snakes[usize::from(self.player_id)]
+    }
+
+    pub fn opponents(&self) -> impl Iterator<Item = &'a Snake> {
+        let player_id = self.player_id;
+        self.state
+            .snakes
+            .iter()
+            .filter(move |snake| snake.player.id != player_id)
+    }
+
+    #[must_use]
+    pub const fn board(&self) -> &'a BoardConfig {
+        &self.state.board
+    }
+
+    #[must_use]
+    pub fn food(&self) -> &'a [Position] {
+        &self.state.food
+    }
+
+    #[must_use]
+    pub const fn tick(&self) -> u64 {
+        self.state.tick
+    }
+
+    #[must_use]
+    pub const fn state(&self) -> &'a GameState {
+        self.state
+    }
+}
+
+/// Input from outside the board, for controllers that are not driven by the board alone.
+/// Apps without a device or a network game simply lack the matching resource.
+#[derive(SystemParam)]
+pub struct ControlInput<'w> {
+    pub keyboard: Option<Res<'w, Input<KeyCode>>>,
+    pub gamepad_buttons: Option<Res<'w, Input<GamepadButton>>>,
+    pub gamepad_axes: Option<Res<'w, Axis<GamepadAxis>>>,
+    pub gamepads: Option<Res<'w, GamepadAssignments>>,
+    pub deadzone: Option<Res<'w, StickDeadzone>>,
+    pub timer: Option<Res<'w, TickTimer>>,
+    pub lockstep: Option<ResMut<'w, Lockstep>>,
+}
+
+impl ControlInput<'_> {
+    /// Whether the tick timer fired this frame.
+    #[must_use]
+    pub fn tick_due(&self) -> bool {
+        self.timer
+            .as_ref()
+            .is_some_and(|timer| timer.just_finished())
+    }
+}
+
+/// Decides where a snake goes next. Asked once per tick, before movement.
+pub trait SnakeController: Send + Sync {
+    /// `None` keeps the current direction.
+    fn next_direction(&mut self, view: BoardView) -> Option<Direction>;
+
+    /// Called every frame while playing, ticks or not, so controllers driven by people or the
+    /// network can collect input between ticks.
+    fn read_input(&mut self, _view: BoardView, _input: &mut ControlInput) {}
+
+    /// Called when a new game starts on the same board, e.g. a rematch.
+    fn restart(&mut self) {}
+}
+
+/// Plays back a fixed list of turns, one per tick, then goes straight.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScriptedController(VecDeque<Option<Direction>>);
+
+impl ScriptedController {
+    #[must_use]
+    pub fn new(turns: impl IntoIterator<Item = Option<Direction>>) -> Self {
+        Self(turns.into_iter().collect())
+    }
+}
+
+impl SnakeController for ScriptedController {
+    fn next_direction(&mut self, _view: BoardView) -> Option<Direction> {
+        self.0.pop_front().flatten()
+    }
+}
+
+/// Several controllers steering one snake, such as a player's keys and pad. Each tick the first
+/// with a turn wins, and the rest keep theirs for later ticks.
+#[derive(Default)]
+pub struct CombinedController(Vec<Box<dyn SnakeController>>);
+
+impl CombinedController {
+    #[must_use]
+    pub fn with(mut self, controller: impl SnakeController + 'static) -> Self {
+        self.0.push(Box::new(controller));
+        self
+    }
+}
+
+impl SnakeController for CombinedController {
+    fn next_direction(&mut self, view: BoardView) -> Option<Direction> {
+        self.0
+            .iter_mut()
+            .find_map(|controller| controller.next_direction(view))
+    }
+
+    fn read_input(&mut self, view: BoardView, input: &mut ControlInput) {
+        for controller in &mut self.0 {
+            controller.read_input(view, input);
+        }
+    }
+
+    fn restart(&mut self) {
+        for controller in &mut self.0 {
+            controller.restart();
+        }
+    }
+}
+
+/// Controllers by `Player::id`, the only way snakes are steered. Players without one go straight.
+#[derive(Resource, Default)]
+pub struct Controllers(Vec<Option<Box<dyn SnakeController>>>);
+
+impl Controllers {
+    #[must_use]
+    pub fn new(players: u8) -> Self {
+        Self(
+            std::iter::repeat_with(|| None)
+                .take(usize::from(players))
+                .collect(),
+        )
+    }
+
+    #[must_use]
+    pub fn with(mut self, player_id: u8, controller: impl SnakeController + 'static) -> Self {
+        self.set(player_id, controller);
+        self
+    }
+
+    pub fn set(&mut self, player_id: u8, controller: impl SnakeController + 'static) {
+        let index = usize::from(player_id);
+        if self.0.len() <= index {
+            self.0.resize_with(index + 1, || None);
+        }
+        self.0[index] = Some(Box::new(controller));
+    }
+
+    pub fn remove(&mut self, player_id: u8) -> Option<Box<dyn SnakeController>> {
+        self.0.get_mut(usize::from(player_id))?.take()
+    }
+
+    #[must_use]
+    pub fn controls(&self, player_id: u8) -> bool {
+        self.0
+            .get(usize::from(player_id))
+            .is_some_and(Option::is_some)
+    }
+
+    /// Asks every controller of a living snake for its next direction, indexed by player id.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn decide(&mut self, state: &GameState) -> Vec<Option<Direction>> {
+        self.0
+            .iter_mut()
+            .enumerate()
+            .map(|(player_id, controller)| {
+                let controller = controller.as_mut()?;
+                let snake = state.snakes.get(player_id)?;
+                if !snake.is_alive() {
+                    return None;
+                }
+                controller.next_direction(BoardView::new(state, player_id as u8))
+            })
+            .collect()
+    }
+
+    /// Hands this frame's input to every controller of a living snake.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn read_input(&mut self, state: &GameState, input: &mut ControlInput) {
+        for (player_id, controller) in self.0.iter_mut().enumerate() {
+            let (Some(controller), Some(snake)) = (controller, state.snakes.get(player_id)) else {
+                continue;
+            };
+            if snake.is_alive() {
+                controller.read_input(BoardView::new(state, player_id as u8), input);
+            }
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.0
+            .iter_mut()
+            .flatten()
+            .for_each(|controller| controller.restart());
+    }
+}
+
+/// Lets every controller read the frame's input. Runs every frame while playing, before the tick.
+#[allow(clippy::needless_pass_by_value)]
+pub fn controller_input_system(
+    state: Res<GameState>,
+    mut input: ControlInput,
+    mut controllers: ResMut<Controllers>,
+) {
+    controllers.read_input(&state, &mut input);
+}
+
+pub fn restart_system(mut controllers: ResMut<Controllers>) {
+    controllers.restart();
+}
+
+/// Steers controlled heads before the tick's movement.
+pub fn controller_system(
+    state: Res<GameState>,
+    mut controllers: ResMut<Controllers>,
+    mut heads: Query<(&mut Head, &Player)>,
+) {
+    let directions = controllers.decide(&state);
+    for (mut head, player) in &mut heads {
+        if let Some(Some(direction)) = directions.get(player.id()) {
+            head.steer(*direction);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        components::GameEndEvent,
+        snake::{self, PlayerConfigs, Segments},
+    };
+
+    /// Turns left until it is heading left.
+    struct LeftTurner;
+
+    impl SnakeController for LeftTurner {
+        fn next_direction(&mut self, view: BoardView) -> Option<Direction> {
+            (view.snake().direction != Direction::Left).then_some(Direction::Left)
+        }
+    }
+
+    #[test]
+    fn view_separates_the_snake_from_its_opponents() {
+        let state = GameState::new(BoardConfig::default(), 3);
+        let view = BoardView::new(&state, 1);
+
+        assert_eq!(view.snake().player.id, 1);
+        assert_eq!(
+            view.opponents()
+                .map(|snake| snake.player.id)
+                .collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+    }
+
+    #[test]
+    fn scripted_controller_plays_its_turns_in_order() {
+        let state = GameState::default();
+        let mut controllers = Controllers::new(2).with(
+            1,
+            ScriptedController::new([Some(Direction::Left), None, Some(Direction::Up)]),
+        );
+
+        let decisions: Vec<_> = (0..4).map(|_| controllers.decide(&state)).collect();
+
+        assert_eq!(
+            decisions,
+            vec![
+                vec![None, Some(Direction::Left)],
+                vec![None, None],
+                vec![None, Some(Direction::Up)],
+                vec![None, None],
+            ]
+        );
+    }
+
+    #[test]
+    fn combined_controllers_keep_turns_they_did_not_get_to_make() {
+        let state = GameState::default();
+        let view = BoardView::new(&state, 0);
+        let mut combined = CombinedController::default()
+            .with(ScriptedController::new([None, Some(Direction::Left)]))
+            .with(ScriptedController::new([Some(Direction::Right)]));
+
+        let decisions: Vec<_> = (0..3).map(|_| combined.next_direction(view)).collect();
+
+        assert_eq!(
+            decisions,
+            vec![Some(Direction::Right), Some(Direction::Left), None]
+        );
+    }
+
+    #[test]
+    fn custom_controller_steers_its_head() {
+        // Setup
+        let mut app = App::new();
+        app.insert_resource(Segments::default())
+            .insert_resource(GameState::default())
+            .init_resource::<PlayerConfigs>()
+            .insert_resource(Controllers::new(2).with(0, LeftTurner))
+            .add_event::<GameEndEvent>()
+            .add_startup_system(snake::spawn_system)
+            .add_systems((controller_system, snake::movement_system).chain());
+
+        // Run systems
+        app.update();
+
+        let mut query = app.world.query::<(&Position, &Player, With<Head>)>();
+        let positions: Vec<_> = query
+            .iter(&app.world)
+            .map(|(position, player, _)| (player.id, position.clone()))
+            .collect();
+        assert!(positions.contains(&(0, Position { x: 2, y: 3 })));
+        assert!(positions.contains(&(1, Position { x: 17, y: 4 })));
+        assert!(app.world.resource::<Controllers>().controls(0));
+        assert!(!app.world.resource::<Controllers>().controls(1));
+    }
+}
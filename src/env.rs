@@ -0,0 +1,499 @@
+use std::collections::HashSet;
+
+use crate::{
+    components::{Collision, Direction, GameEndEvent, Player, Position},
+    controller::Controllers,
+    food::{FoodConfig, FoodSpawn},
+    grid::{BoardConfig, EdgePolicy},
+    rng::GameRng,
+    simulation::{next_position, GameEvent, GameState},
+};
+
+/// Observation planes, in the order they are stacked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Channel {
+    OwnHead,
+    OwnBody,
+    /// Every cell of every other snake, heads included. Dead snakes stay on the board and still
+    /// kill, so they are marked here too.
+    Opponents,
+    Food,
+    /// The one-cell border around the board, when its edges are walls.
+    Walls,
+}
+
+impl Channel {
+    pub const ALL: [Self; 5] = [
+        Self::OwnHead,
+        Self::OwnBody,
+        Self::Opponents,
+        Self::Food,
+        Self::Walls,
+    ];
+}
+
+/// One player's view of the board as stacked 0/1 planes, channel-major (`[channel][y][x]`).
+///
+/// The planes are two cells wider and taller than the board so that walls have somewhere to go:
+/// board cell `(x, y)` is at `(x + 1, y + 1)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<f32>,
+}
+
+impl Observation {
+    #[must_use]
+    pub fn shape(board: &BoardConfig) -> (usize, usize, usize) {
+        (
+            Channel::ALL.len(),
+            usize::from(board.height) + 2,
+            usize::from(board.width) + 2,
+        )
+    }
+
+    /// Encodes `state` as seen by `player_id`. A dead player still sees the board, minus itself.
+    #[must_use]
+    pub fn encode(state: &GameState, player_id: u8) -> Self {
+        let (channels, height, width) = Self::shape(&state.board);
+        let mut data = vec![0.; channels * width * height];
+        Self::encode_into(state, player_id, &mut data);
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Like `encode`, but writes into `data`, which must be zeroed and hold exactly one observation.
+    pub fn encode_into(state: &GameState, player_id: u8, data: &mut [f32]) {
+        let (_, height, width) = Self::shape(&state.board);
+        let mut observation = ObservationMut {
+            width,
+            height,
+            data,
+        };
+
+        for snake in &state.snakes {
+            if snake.player.id == player_id {
+                if snake.is_alive() {
+                    observation.mark(Channel::OwnHead, snake.head());
+                    for position in snake.body.iter().skip(1) {
+                        observation.mark(Channel::OwnBody, position);
+                    }
+                }
+            } else {
+                for position in &snake.body {
+                    observation.mark(Channel::Opponents, position);
+                }
+            }
+        }
+        for position in &state.food {
+            observation.mark(Channel::Food, position);
+        }
+        if state.board.edges == EdgePolicy::Walls {
+            for y in 0..height {
+                for x in 0..width {
+                    if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                        observation.data[index(width, height, Channel::Walls, x, y)] = 1.;
+                    }
+                }
+            }
+        }
+    }
+
+    /// The value at board cell `position`, which may be one cell outside the board.
+    #[allow(clippy::cast_sign_loss)]
+    #[must_use]
+    pub fn get(&self, channel: Channel, position: &Position) -> f32 {
+        let (x, y) = (position.x + 1, position.y + 1);
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return 0.;
+        }
+        self.data[index(self.width, self.height, channel, x as usize, y as usize)]
+    }
+}
+
+const fn index(width: usize, height: usize, channel: Channel, x: usize, y: usize) -> usize {
+    (channel as usize * height + y) * width + x
+}
+
+struct ObservationMut<'a> {
+    width: usize,
+    height: usize,
+    data: &'a mut [f32],
+}
+
+impl ObservationMut<'_> {
+    #[allow(clippy::cast_sign_loss)]
+    fn mark(&mut self, channel: Channel, position: &Position) {
+        let (x, y) = (position.x + 1, position.y + 1);
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            self.data[index(self.width, self.height, channel, x as usize, y as usize)] = 1.;
+        }
+    }
+}
+
+/// What each kind of event is worth to the learning player. Everything is summed per step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RewardShaping {
+    pub food: f32,
+    pub death: f32,
+    pub win: f32,
+    /// Paid every step the snake is still alive afterwards.
+    pub survival: f32,
+    /// Paid per cell the head gets closer to the nearest food, and charged per cell further.
+    pub approach: f32,
+}
+
+impl Default for RewardShaping {
+    fn default() -> Self {
+        Self {
+            food: 1.,
+            death: -1.,
+            win: 1.,
+            survival: 0.,
+            approach: 0.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EnvConfig {
+    pub board: BoardConfig,
+    /// Player 0 learns; the others are driven by the env's opponents.
+    pub players: u8,
+    pub food: FoodConfig,
+    pub rewards: RewardShaping,
+    /// Episodes still running after this many ticks end without a death.
+    pub max_ticks: u64,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        Self {
+            board: BoardConfig::new(10, 10),
+            players: 1,
+            food: FoodConfig {
+                max_food: 1,
+                spawn: FoodSpawn::OnEat,
+            },
+            rewards: RewardShaping::default(),
+            max_ticks: 1_000,
+        }
+    }
+}
+
+/// Extra details about a step, for logging rather than learning.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StepInfo {
+    pub tick: u64,
+    pub length: usize,
+    pub ate: bool,
+    pub collision: Option<Collision>,
+    pub outcome: Option<GameEndEvent>,
+    /// The episode hit `EnvConfig::max_ticks`.
+    pub truncated: bool,
+}
+
+/// Gym-style wrapper around `GameState` for training an agent as player 0.
+pub struct SnakeEnv {
+    config: EnvConfig,
+    state: GameState,
+    rng: GameRng,
+    opponents: Controllers,
+}
+
+/// The player whose moves come from `SnakeEnv::step`.
+pub const LEARNER: u8 = 0;
+
+impl SnakeEnv {
+    #[must_use]
+    pub fn new(config: EnvConfig) -> Self {
+        let mut env = Self {
+            config,
+            state: GameState::new(config.board, config.players).with_food(config.food),
+            rng: GameRng::new(0),
+            opponents: Controllers::new(config.players),
+        };
+        env.reset(0);
+        env
+    }
+
+    /// Opponents for players other than 0. Players without a controller go straight.
+    #[must_use]
+    pub fn with_opponents(mut self, opponents: Controllers) -> Self {
+        self.opponents = opponents;
+        self
+    }
+
+    #[must_use]
+    pub const fn config(&self) -> &EnvConfig {
+        &self.config
+    }
+
+    #[must_use]
+    pub const fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// Starts a new episode. The same seed always gives the same episode for the same actions.
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.rng = GameRng::new(seed);
+        self.state.restart();
+        self.state.refill_food(&mut self.rng);
+        self.opponents.restart();
+        self.observe()
+    }
+
+    #[must_use]
+    pub fn observe(&self) -> Observation {
+        Observation::encode(&self.state, LEARNER)
+    }
+
+    /// Moves the learner in `action`'s direction (`None` goes straight) and everyone else as
+    /// their controllers decide.
+    pub fn step(&mut self, action: Option<Direction>) -> (Observation, f32, bool, StepInfo) {
+        let (reward, done, info) = self.advance(action);
+        (self.observe(), reward, done, info)
+    }
+
+    /// `step` without encoding the observation, for callers that encode it themselves.
+    pub fn advance(&mut self, action: Option<Direction>) -> (f32, bool, StepInfo) {
+        let rewards = self.config.rewards;
+        let distance_before = self.food_distance();
+
+        let mut inputs = self.opponents.decide(&self.state);
+        inputs.resize(self.state.snakes.len(), None);
+        inputs[usize::from(LEARNER)] = action;
+        let events = self.state.step(&inputs);
+        self.state.spawn_due_food(&mut self.rng);
+
+        let learner = &self.state.snakes[usize::from(LEARNER)];
+        let ate = events.iter().any(
+            |event| matches!(event, GameEvent::Ate { player_id, .. } if *player_id == LEARNER),
+        );
+        let truncated = self.state.tick >= self.config.max_ticks;
+        let done = !learner.is_alive() || self.state.outcome.is_some() || truncated;
+
+        let mut reward = 0.;
+        if ate {
+            reward += rewards.food;
+        }
+        if learner.is_alive() {
+            reward += rewards.survival;
+            if let (Some(before), Some(after)) = (distance_before, self.food_distance()) {
+                reward += rewards.approach * (before as f32 - after as f32);
+            }
+        } else {
+            reward += rewards.death;
+        }
+        if self.state.outcome == Some(GameEndEvent::Winner(Player { id: LEARNER }))
+            && self.state.snakes.len() > 1
+        {
+            reward += rewards.win;
+        }
+
+        let info = StepInfo {
+            tick: self.state.tick,
+            length: learner.body.len(),
+            ate,
+            collision: learner.collision,
+            outcome: self.state.outcome.clone(),
+            truncated,
+        };
+        (reward, done, info)
+    }
+
+    /// Manhattan distance from the learner's head to the nearest food, ignoring wrapping.
+    fn food_distance(&self) -> Option<u32> {
+        let head = self.state.snakes[usize::from(LEARNER)].head();
+        self.state
+            .food
+            .iter()
+            .map(|food| u32::from(food.x.abs_diff(head.x)) + u32::from(food.y.abs_diff(head.y)))
+            .min()
+    }
+
+    /// Cells the learner could move into without dying right away, for action masking.
+    #[must_use]
+    pub fn safe_actions(&self) -> Vec<Direction> {
+        let snake = &self.state.snakes[usize::from(LEARNER)];
+        let blocked: HashSet<&Position> = self
+            .state
+            .snakes
+            .iter()
+            .flat_map(|snake| snake.body.iter())
+            .collect();
+        Direction::ALL
+            .into_iter()
+            .filter(|direction| *direction != snake.direction.opposite())
+            .filter(|direction| {
+                let next = self
+                    .state
+                    .board
+                    .wrap(next_position(snake.head(), *direction));
+                self.state.board.contains(&next) && !blocked.contains(&next)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bot::{BotController, BotLevel};
+
+    #[test]
+    fn observation_marks_every_channel() {
+        let mut state = GameState::new(BoardConfig::new(10, 10), 2);
+        state.food = vec![Position { x: 5, y: 5 }];
+
+        let observation = Observation::encode(&state, 1);
+
+        assert_eq!(observation.data.len(), 5 * 12 * 12);
+        let own = &state.snakes[1];
+        assert_eq!(observation.get(Channel::OwnHead, own.head()), 1.);
+        assert_eq!(observation.get(Channel::OwnBody, &own.body[1]), 1.);
+        assert_eq!(observation.get(Channel::OwnBody, own.head()), 0.);
+        for position in &state.snakes[0].body {
+            assert_eq!(observation.get(Channel::Opponents, position), 1.);
+        }
+        assert_eq!(observation.get(Channel::Food, &Position { x: 5, y: 5 }), 1.);
+        assert_eq!(
+            observation.get(Channel::Walls, &Position { x: -1, y: 4 }),
+            1.
+        );
+        assert_eq!(
+            observation.get(Channel::Walls, &Position { x: 0, y: 4 }),
+            0.
+        );
+        assert_eq!(
+            observation.data.iter().sum::<f32>(),
+            1. + 1. + 2. + 1. + 44.
+        );
+    }
+
+    #[test]
+    fn dead_opponents_still_block() {
+        let mut state = GameState::new(BoardConfig::new(10, 10), 3);
+        state.snakes[2].collision = Some(Collision::Wall);
+
+        let observation = Observation::encode(&state, 0);
+
+        for position in &state.snakes[2].body {
+            assert_eq!(observation.get(Channel::Opponents, position), 1.);
+        }
+    }
+
+    #[test]
+    fn wrapping_boards_have_no_walls() {
+        let state = GameState::new(BoardConfig::new(6, 6).with_edges(EdgePolicy::Wrap), 1);
+
+        let observation = Observation::encode(&state, 0);
+
+        assert_eq!(
+            observation.get(Channel::Walls, &Position { x: -1, y: 0 }),
+            0.
+        );
+    }
+
+    #[test]
+    fn reset_is_deterministic_per_seed() {
+        let mut env = SnakeEnv::new(EnvConfig::default());
+        let first = env.reset(9);
+        let first_steps: Vec<_> = (0..20).map(|_| env.step(None).1).collect();
+        let second = env.reset(9);
+        let second_steps: Vec<_> = (0..20).map(|_| env.step(None).1).collect();
+
+        assert_eq!(first, second);
+        assert_eq!(first_steps, second_steps);
+    }
+
+    #[test]
+    fn eating_and_dying_are_rewarded_as_configured() {
+        let mut env = SnakeEnv::new(EnvConfig {
+            rewards: RewardShaping {
+                food: 5.,
+                death: -3.,
+                survival: 0.5,
+                ..RewardShaping::default()
+            },
+            ..EnvConfig::default()
+        });
+        env.reset(1);
+        // Food right in front of the head, which starts at (3, 3) heading up.
+        env.state.food = vec![Position { x: 3, y: 4 }];
+
+        let (_, reward, done, info) = env.step(None);
+        assert_eq!((reward, done), (5.5, false));
+        assert!(info.ate);
+
+        let mut last = (0., false, StepInfo::default());
+        while !last.1 {
+            let (_, reward, done, info) = env.step(None);
+            last = (reward, done, info);
+        }
+        assert_eq!(last.0, -3.);
+        assert_eq!(last.2.collision, Some(Collision::Wall));
+        assert!(!last.2.truncated);
+    }
+
+    #[test]
+    fn approaching_food_is_shaped() {
+        let mut env = SnakeEnv::new(EnvConfig {
+            rewards: RewardShaping {
+                food: 0.,
+                approach: 0.1,
+                ..RewardShaping::default()
+            },
+            ..EnvConfig::default()
+        });
+        env.reset(1);
+        env.state.food = vec![Position { x: 3, y: 8 }];
+
+        assert!((env.step(Some(Direction::Up)).1 - 0.1).abs() < f32::EPSILON);
+        assert!((env.step(Some(Direction::Right)).1 + 0.1).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn episodes_are_truncated_at_max_ticks() {
+        let mut env = SnakeEnv::new(EnvConfig {
+            max_ticks: 5,
+            ..EnvConfig::default()
+        });
+        env.reset(2);
+
+        let mut steps = 0;
+        let info = loop {
+            steps += 1;
+            let action = env.safe_actions().first().copied();
+            let (_, _, done, info) = env.step(action);
+            if done {
+                break info;
+            }
+        };
+
+        assert_eq!(steps, 5);
+        assert!(info.truncated);
+    }
+
+    #[test]
+    fn opponents_play_against_the_learner() {
+        let mut env = SnakeEnv::new(EnvConfig {
+            players: 2,
+            ..EnvConfig::default()
+        })
+        .with_opponents(Controllers::new(2).with(1, BotController(BotLevel::Safe)));
+        env.reset(3);
+
+        // Going straight up, player 1 would hit the top wall on the seventh tick.
+        for _ in 0..10 {
+            let action = env.safe_actions().first().copied();
+            env.step(action);
+        }
+
+        assert!(env.state().snakes[1].is_alive());
+    }
+}
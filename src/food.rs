@@ -1,67 +1,84 @@
-use std::collections::HashSet;
-
 use crate::{
     components::{Position, Size},
-    grid::{GRID_HEIGHT, GRID_WIDTH},
+    rng::GameRng,
+    simulation::GameState,
 };
 use bevy::prelude::*;
-use rand::prelude::random;
+use serde::{Deserialize, Serialize};
 
 const FOOD_COLOR: Color = Color::rgb(1.0, 1.0, 1.0);
 
 #[derive(Component)]
 pub struct Food;
 
-#[allow(clippy::cast_possible_wrap)]
-pub fn spawn_system(mut commands: Commands, positions: Query<&Position>) {
-    let positions_set: HashSet<&Position> = positions.iter().collect();
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FoodSpawn {
+    /// One food every `n` game ticks.
+    Interval(u64),
+    /// Tops the board back up to the cap as soon as food is eaten.
+    OnEat,
+}
 
-    if let Some(position) = (0..(GRID_WIDTH * GRID_HEIGHT))
-        .map(|_| Position {
-            x: if cfg!(test) {
-                3
-            } else {
-                (random::<u16>() % GRID_WIDTH) as i16
-            },
-            y: if cfg!(test) {
-                5
-            } else {
-                (random::<u16>() % GRID_HEIGHT) as i16
-            },
-        })
-        .find(|position| !positions_set.contains(position))
-    {
-        commands
-            .spawn((
-                SpriteBundle {
-                    sprite: Sprite {
-                        color: FOOD_COLOR,
-                        ..default()
-                    },
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FoodConfig {
+    pub max_food: usize,
+    pub spawn: FoodSpawn,
+}
+
+impl Default for FoodConfig {
+    fn default() -> Self {
+        Self {
+            max_food: 3,
+            spawn: FoodSpawn::Interval(7),
+        }
+    }
+}
+
+pub fn spawn_system(
+    mut commands: Commands,
+    mut state: ResMut<GameState>,
+    mut rng: ResMut<GameRng>,
+) {
+    for position in state.spawn_due_food(rng.as_mut()) {
+        spawn_food_entity(&mut commands, position);
+    }
+}
+
+pub fn spawn_food_entity(commands: &mut Commands, position: Position) {
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: FOOD_COLOR,
                     ..default()
                 },
-                Food,
-                Size::square(0.65),
-            ))
-            .insert(position);
-    }
+                ..default()
+            },
+            Food,
+            Size::square(0.65),
+        ))
+        .insert(position);
 }
 
 #[cfg(test)]
 mod test {
-    use crate::components::Position;
+    use std::collections::HashSet;
+
+    use crate::grid::BoardConfig;
 
     use super::*;
     use proptest::prelude::*;
 
     proptest! {
         #[test]
-        fn spawns_food_inplace(_execution in 0u32..1000) {
+        fn spawns_food_inplace(seed in any::<u64>()) {
             // Setup app
             let mut app = App::new();
 
             // Add startup system
-            app.add_startup_system(spawn_system);
+            app.insert_resource(GameState::default())
+                .insert_resource(GameRng::new(seed))
+                .add_startup_system(spawn_system);
 
             // Run systems
             app.update();
@@ -72,19 +89,23 @@ mod test {
                 let x = position.x;
                 let y = position.y;
 
-                assert!(x >= 0 && x as i32 <= (GRID_WIDTH -1) as i32);
-                assert!(y >= 0 && y as i32 <= (GRID_HEIGHT -1) as i32);
+                assert!(BoardConfig::default().contains(&Position { x, y }));
             })
         }
     }
 
     #[test]
-    fn food_only_spawns_once() {
+    fn food_spawns_once_per_run() {
         // Setup
         let mut app = App::new();
 
         // Add systems
-        app.add_system(spawn_system);
+        app.insert_resource(GameState::default().with_food(FoodConfig {
+            max_food: 3,
+            spawn: FoodSpawn::Interval(1),
+        }))
+        .insert_resource(GameRng::new(0))
+        .add_system(spawn_system);
 
         // Run systems
         app.update();
@@ -96,6 +117,65 @@ mod test {
         app.update();
 
         let mut query = app.world.query::<(&Food, &Position)>();
-        assert_eq!(query.iter(&app.world).count(), 1)
+        let positions: HashSet<&Position> = query.iter(&app.world).map(|(_, p)| p).collect();
+        assert_eq!(positions.len(), 2);
+        assert_eq!(app.world.resource::<GameState>().food.len(), 2);
+    }
+
+    #[test]
+    fn same_seed_places_the_same_food() {
+        let place = |seed| {
+            let mut app = App::new();
+            app.insert_resource(GameState::default())
+                .insert_resource(GameRng::new(seed))
+                .add_system(spawn_system);
+            for _ in 0..5 {
+                app.update();
+            }
+            app.world.resource::<GameState>().food.clone()
+        };
+
+        assert_eq!(place(7), place(7));
+    }
+
+    #[test]
+    fn refill_keeps_the_board_at_the_food_cap() {
+        // Setup
+        let mut app = App::new();
+        let state = GameState::default().with_food(FoodConfig {
+            max_food: 4,
+            spawn: FoodSpawn::OnEat,
+        });
+
+        // Add systems
+        app.insert_resource(state)
+            .insert_resource(GameRng::new(1))
+            .add_system(spawn_system);
+
+        // Run systems
+        app.update();
+
+        let mut query = app.world.query_filtered::<Entity, With<Food>>();
+        assert_eq!(query.iter(&app.world).count(), 4);
+
+        // Eat one, the way eating_system does: drop it from the state and despawn its entity.
+        let eaten = app.world.resource_mut::<GameState>().food.pop().unwrap();
+        let mut query = app
+            .world
+            .query_filtered::<(Entity, &Position), With<Food>>();
+        let entity = query
+            .iter(&app.world)
+            .find_map(|(entity, position)| (*position == eaten).then_some(entity))
+            .unwrap();
+        app.world.despawn(entity);
+        app.update();
+
+        assert_eq!(app.world.resource::<GameState>().food.len(), 4);
+        let mut query = app.world.query_filtered::<&Position, With<Food>>();
+        let mut entities: Vec<Position> = query.iter(&app.world).cloned().collect();
+        let mut food = app.world.resource::<GameState>().food.clone();
+        entities.sort_by_key(|position| (position.x, position.y));
+        food.sort_by_key(|position| (position.x, position.y));
+        assert_eq!(entities, food);
     }
 }
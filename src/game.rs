@@ -1,20 +1,186 @@
 use bevy::prelude::*;
 
-use crate::components::GameEndEvent;
+use crate::{
+    bindings::InputBindings,
+    components::{Collision, GameEndEvent, Player},
+    food::Food,
+    simulation::GameState,
+    snake::{Segment, Segments},
+};
 
-pub fn game_over_system(mut commands: Commands, mut reader: EventReader<GameEndEvent>) {
-    if reader.iter().next().is_some() {
-        commands.spawn_empty().insert(GameEndEvent::GameOver);
-        println!("{}", GameEndEvent::GameOver);
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AppState {
+    #[default]
+    MainMenu,
+    Countdown,
+    Playing,
+    Paused,
+    GameOver,
+}
+
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct Countdown(pub Timer);
+
+impl Default for Countdown {
+    fn default() -> Self {
+        Self(Timer::from_seconds(3.0, TimerMode::Once))
+    }
+}
+
+#[derive(Default, Resource, Debug, PartialEq, Eq)]
+pub struct GameResult {
+    pub outcome: Option<GameEndEvent>,
+    pub collisions: Vec<(Player, Collision)>,
+}
+
+pub fn main_menu_system() {
+    println!("Press Space to start");
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn menu_input_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::Space, KeyCode::Return]) {
+        next_state.set(AppState::Countdown);
+    }
+}
+
+pub fn start_countdown_system(mut countdown: ResMut<Countdown>) {
+    countdown.reset();
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn countdown_system(
+    time: Res<Time>,
+    mut countdown: ResMut<Countdown>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if countdown.tick(time.delta()).finished() {
+        next_state.set(AppState::Playing);
     }
 }
 
+#[allow(clippy::needless_pass_by_value)]
+pub fn pause_input_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<InputBindings>,
+    app_state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if !bindings.pause_just_pressed(&keyboard_input) {
+        return;
+    }
+    match app_state.0 {
+        AppState::Playing => {
+            println!("Paused");
+            next_state.set(AppState::Paused);
+        }
+        AppState::Paused => next_state.set(AppState::Playing),
+        AppState::MainMenu | AppState::Countdown | AppState::GameOver => (),
+    }
+}
+
+/// Results carried across rematches, indexed by `Player::id`.
+#[derive(Default, Resource, Debug, PartialEq, Eq)]
+pub struct MatchScore {
+    pub wins: Vec<u32>,
+    pub draws: u32,
+    pub games: u32,
+}
+
+impl MatchScore {
+    pub fn record(&mut self, outcome: &GameEndEvent) {
+        self.games += 1;
+        match outcome {
+            GameEndEvent::Winner(player) => {
+                if self.wins.len() <= player.id() {
+                    self.wins.resize(player.id() + 1, 0);
+                }
+                self.wins[player.id()] += 1;
+            }
+            GameEndEvent::Draw => self.draws += 1,
+            GameEndEvent::AllDead => (),
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn game_over_system(
+    mut reader: EventReader<GameEndEvent>,
+    state: Res<GameState>,
+    mut result: ResMut<GameResult>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if let Some(game_end) = reader.iter().next() {
+        println!("{game_end}");
+        next_state.set(AppState::GameOver);
+        *result = GameResult {
+            outcome: Some(game_end.clone()),
+            collisions: state
+                .snakes
+                .iter()
+                .filter_map(|snake| Some((snake.player.clone(), snake.collision?)))
+                .collect(),
+        };
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn record_match_system(result: Res<GameResult>, mut score: ResMut<MatchScore>) {
+    if let Some(outcome) = &result.outcome {
+        score.record(outcome);
+        let wins: Vec<String> = score
+            .wins
+            .iter()
+            .enumerate()
+            .map(|(id, wins)| format!("P{} {wins}", id + 1))
+            .collect();
+        println!(
+            "Match after {} games: {} (draws {})",
+            score.games,
+            wins.join(" - "),
+            score.draws
+        );
+    }
+    println!("Press R to play again");
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn rematch_input_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::R, KeyCode::Space, KeyCode::Return]) {
+        next_state.set(AppState::Countdown);
+    }
+}
+
+/// Clears the finished game so `snake::spawn_system` can start the next one.
+#[allow(clippy::type_complexity)]
+pub fn reset_game_system(
+    mut commands: Commands,
+    mut state: ResMut<GameState>,
+    mut segments: ResMut<Segments>,
+    mut result: ResMut<GameResult>,
+    entities: Query<Entity, Or<(With<Segment>, With<Food>)>>,
+) {
+    entities.for_each(|entity| commands.entity(entity).despawn());
+    segments.clear();
+    state.restart();
+    *result = GameResult::default();
+}
+
 #[cfg(test)]
 pub mod test {
 
     use super::*;
     use crate::components::Position;
-    use crate::snake::{self, Head, LastTailPosition, Segments};
+    use crate::controller;
+    use crate::rng::GameRng;
+    use crate::simulation::GameState;
+    use crate::snake::{self, Head, PlayerConfigs, Segments};
 
     #[test]
     fn game_end_event_with_game_over() {
@@ -23,11 +189,22 @@ pub mod test {
 
         // Add systems
         app.insert_resource(Segments::default())
-            .insert_resource(LastTailPosition::default())
+            .insert_resource(GameState::default())
+            .init_resource::<PlayerConfigs>()
+            .insert_resource(InputBindings::default().controllers())
+            .insert_resource(GameResult::default())
+            .add_state::<AppState>()
             .add_event::<GameEndEvent>()
             .add_startup_system(snake::spawn_system)
             .add_system(snake::movement_system)
-            .add_system(snake::movement_input_system.before(snake::movement_system))
+            .add_systems(
+                (
+                    controller::controller_input_system,
+                    controller::controller_system,
+                )
+                    .chain()
+                    .before(snake::movement_system),
+            )
             .add_system(game_over_system.after(snake::movement_system));
 
         // Add new input resource
@@ -40,15 +217,26 @@ pub mod test {
             app.update(); // 3 + _
         }
 
-        let mut query = app.world.query::<&GameEndEvent>();
-        assert_eq!(query.iter(&app.world).count(), 0);
+        assert_eq!(app.world.resource::<GameResult>().outcome, None);
 
         for _ in 0..20 {
             app.update();
         }
 
-        let mut query = app.world.query::<&GameEndEvent>();
-        assert_eq!(query.iter(&app.world).count(), 2);
+        assert_eq!(
+            app.world.resource::<State<AppState>>().0,
+            AppState::GameOver
+        );
+        assert_eq!(
+            app.world.resource::<GameResult>(),
+            &GameResult {
+                outcome: Some(GameEndEvent::Draw),
+                collisions: vec![
+                    (Player { id: 0 }, Collision::Wall),
+                    (Player { id: 1 }, Collision::Wall)
+                ],
+            }
+        );
 
         let mut query = app.world.query_filtered::<&Position, With<Head>>();
         let position_at_gameover = query.iter(&app.world).next().unwrap();
@@ -72,11 +260,22 @@ pub mod test {
 
         // Add systems
         app.insert_resource(Segments::default())
-            .insert_resource(LastTailPosition::default())
+            .insert_resource(GameState::default())
+            .init_resource::<PlayerConfigs>()
+            .insert_resource(InputBindings::default().controllers())
+            .insert_resource(GameResult::default())
+            .add_state::<AppState>()
             .add_event::<GameEndEvent>()
             .add_startup_system(snake::spawn_system)
             .add_system(snake::movement_system)
-            .add_system(snake::movement_input_system.before(snake::movement_system))
+            .add_systems(
+                (
+                    controller::controller_input_system,
+                    controller::controller_system,
+                )
+                    .chain()
+                    .before(snake::movement_system),
+            )
             .add_system(game_over_system.after(snake::movement_system));
 
         // Add new input resource
@@ -89,13 +288,21 @@ pub mod test {
         app.update(); // x: 1, y: 3
         app.update(); // x: 0, y: 3
 
-        let mut query = app.world.query::<&GameEndEvent>();
-        assert_eq!(query.iter(&app.world).count(), 0);
+        assert_eq!(app.world.resource::<GameResult>().outcome, None);
 
         app.update(); // x: -1, y: 3
 
-        let mut query = app.world.query::<&GameEndEvent>();
-        assert_eq!(query.iter(&app.world).count(), 1);
+        assert_eq!(
+            app.world.resource::<NextState<AppState>>().0,
+            Some(AppState::GameOver)
+        );
+        assert_eq!(
+            app.world.resource::<GameResult>(),
+            &GameResult {
+                outcome: Some(GameEndEvent::Winner(Player { id: 1 })),
+                collisions: vec![(Player { id: 0 }, Collision::Wall)],
+            }
+        );
     }
 
     #[test]
@@ -105,27 +312,208 @@ pub mod test {
 
         // Add systems
         app.insert_resource(Segments::default())
-            .insert_resource(LastTailPosition::default())
+            .insert_resource(GameState::default())
+            .init_resource::<PlayerConfigs>()
+            .insert_resource(InputBindings::default().controllers())
+            .insert_resource(GameResult::default())
+            .add_state::<AppState>()
             .add_event::<GameEndEvent>()
             .add_startup_system(snake::spawn_system)
             .add_system(snake::movement_system)
-            .add_system(snake::movement_input_system.before(snake::movement_system))
+            .add_systems(
+                (
+                    controller::controller_input_system,
+                    controller::controller_system,
+                )
+                    .chain()
+                    .before(snake::movement_system),
+            )
             .add_system(game_over_system.after(snake::movement_system));
 
         // Add new input resource
         let mut input = Input::<KeyCode>::default();
-        #[cfg(debug_assertions)]
         input.press(KeyCode::D);
-        #[cfg(not(debug_assertions))]
-        input.press(KeyCode::Right);
         app.insert_resource(input);
 
         // Run systems again
-        for _ in 0..7 {
+        for _ in 0..17 {
             app.update(); // 3 + _
         }
 
-        let mut query = app.world.query::<&GameEndEvent>();
-        assert_eq!(query.iter(&app.world).count(), 1);
+        assert_eq!(
+            app.world.resource::<NextState<AppState>>().0,
+            Some(AppState::GameOver)
+        );
+        assert_eq!(
+            app.world.resource::<GameResult>().outcome,
+            Some(GameEndEvent::Draw)
+        );
+    }
+
+    fn press(app: &mut App, key: KeyCode) {
+        let mut input = Input::<KeyCode>::default();
+        input.press(key);
+        app.insert_resource(input);
+        app.update();
+        app.insert_resource(Input::<KeyCode>::default());
+    }
+
+    fn current_state(app: &App) -> AppState {
+        app.world.resource::<State<AppState>>().0
+    }
+
+    #[test]
+    fn space_starts_countdown_that_leads_into_playing() {
+        // Setup
+        let mut app = App::new();
+
+        // Add systems
+        app.add_state::<AppState>()
+            .init_resource::<Time>()
+            .insert_resource(Countdown(Timer::from_seconds(0., TimerMode::Once)))
+            .insert_resource(Input::<KeyCode>::default())
+            .add_system(menu_input_system.in_set(OnUpdate(AppState::MainMenu)))
+            .add_system(start_countdown_system.in_schedule(OnEnter(AppState::Countdown)))
+            .add_system(countdown_system.in_set(OnUpdate(AppState::Countdown)));
+
+        app.update();
+        assert_eq!(current_state(&app), AppState::MainMenu);
+
+        press(&mut app, KeyCode::Space);
+        app.update();
+        assert_eq!(current_state(&app), AppState::Countdown);
+
+        app.update();
+        assert_eq!(current_state(&app), AppState::Playing);
+    }
+
+    #[test]
+    fn pausing_stops_the_snakes_until_resumed() {
+        // Setup
+        let mut app = App::new();
+
+        // Add systems
+        app.insert_resource(Segments::default())
+            .insert_resource(GameState::default())
+            .init_resource::<PlayerConfigs>()
+            .init_resource::<InputBindings>()
+            .insert_resource(Input::<KeyCode>::default())
+            .add_state::<AppState>()
+            .add_event::<GameEndEvent>()
+            .add_startup_system(snake::spawn_system)
+            .add_system(pause_input_system)
+            .add_system(snake::movement_system.in_set(OnUpdate(AppState::Playing)));
+
+        app.world
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Playing);
+        app.update();
+        assert_eq!(app.world.resource::<GameState>().snakes[0].head().y, 4);
+
+        press(&mut app, KeyCode::P);
+        app.update();
+        app.update();
+        assert_eq!(current_state(&app), AppState::Paused);
+        assert_eq!(app.world.resource::<GameState>().snakes[0].head().y, 5);
+
+        press(&mut app, KeyCode::P);
+        assert_eq!(current_state(&app), AppState::Paused);
+        app.update();
+        assert_eq!(current_state(&app), AppState::Playing);
+        assert_eq!(app.world.resource::<GameState>().snakes[0].head().y, 6);
+    }
+
+    #[test]
+    fn match_score_counts_wins_and_draws() {
+        let mut score = MatchScore::default();
+
+        score.record(&GameEndEvent::Winner(Player { id: 1 }));
+        score.record(&GameEndEvent::Draw);
+        score.record(&GameEndEvent::Winner(Player { id: 1 }));
+
+        assert_eq!(
+            score,
+            MatchScore {
+                wins: vec![0, 2],
+                draws: 1,
+                games: 3
+            }
+        );
+    }
+
+    #[test]
+    fn rematch_resets_the_board_and_keeps_the_match_score() {
+        // Setup
+        let mut app = App::new();
+
+        // Add systems
+        app.insert_resource(Segments::default())
+            .insert_resource(GameState::default())
+            .init_resource::<PlayerConfigs>()
+            .insert_resource(InputBindings::default().controllers())
+            .init_resource::<GameResult>()
+            .init_resource::<MatchScore>()
+            .insert_resource(GameRng::new(0))
+            .insert_resource(Input::<KeyCode>::default())
+            .add_state::<AppState>()
+            .add_event::<GameEndEvent>()
+            .add_event::<snake::GrowthEvent>()
+            .add_startup_system(snake::spawn_system)
+            .add_systems(
+                (
+                    crate::food::spawn_system.before(snake::movement_system),
+                    snake::movement_system,
+                    snake::eating_system.after(snake::movement_system),
+                    snake::growth_system.after(snake::eating_system),
+                    controller::controller_input_system.before(controller::controller_system),
+                    controller::controller_system.before(snake::movement_system),
+                    game_over_system.after(snake::movement_system),
+                )
+                    .in_set(OnUpdate(AppState::Playing)),
+            )
+            .add_system(record_match_system.in_schedule(OnEnter(AppState::GameOver)))
+            .add_system(rematch_input_system.in_set(OnUpdate(AppState::GameOver)))
+            .add_systems(
+                (reset_game_system, snake::spawn_system)
+                    .chain()
+                    .in_schedule(OnExit(AppState::GameOver)),
+            );
+
+        app.world
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Playing);
+        let mut input = Input::<KeyCode>::default();
+        input.press(KeyCode::A);
+        app.insert_resource(input);
+        for _ in 0..5 {
+            app.update();
+        }
+        assert_eq!(current_state(&app), AppState::GameOver);
+        let mut query = app.world.query_filtered::<Entity, With<Segment>>();
+        assert_eq!(query.iter(&app.world).count(), 4);
+        let food = app.world.resource::<GameState>().food.len();
+        assert!(food > 0);
+        let mut query = app.world.query_filtered::<Entity, With<Food>>();
+        assert_eq!(query.iter(&app.world).count(), food);
+
+        press(&mut app, KeyCode::R);
+        app.update();
+
+        assert_eq!(current_state(&app), AppState::Countdown);
+        assert_eq!(app.world.resource::<GameState>(), &GameState::default());
+        assert_eq!(app.world.resource::<GameResult>(), &GameResult::default());
+        assert_eq!(
+            app.world.resource::<MatchScore>(),
+            &MatchScore {
+                wins: vec![0, 1],
+                draws: 0,
+                games: 1
+            }
+        );
+        let mut query = app.world.query_filtered::<Entity, With<Segment>>();
+        assert_eq!(query.iter(&app.world).count(), 4);
+        let mut query = app.world.query_filtered::<Entity, With<Food>>();
+        assert_eq!(query.iter(&app.world).count(), 0);
+        assert_eq!(app.world.resource::<Segments>().len(), 2);
     }
 }
@@ -0,0 +1,325 @@
+use bevy::{
+    input::gamepad::{GamepadConnection, GamepadConnectionEvent},
+    prelude::*,
+};
+
+use crate::{
+    components::Direction,
+    controller::{BoardView, ControlInput, SnakeController},
+    snake::TurnQueue,
+};
+
+/// Pads driving each player, indexed by `Player::id`.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Eq, Deref)]
+pub struct GamepadAssignments(Vec<Option<Gamepad>>);
+
+impl GamepadAssignments {
+    #[must_use]
+    pub fn new(players: u8) -> Self {
+        Self(vec![None; usize::from(players)])
+    }
+
+    /// Gives `gamepad` to the lowest player id without a pad. Returns the player id.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn assign(&mut self, gamepad: Gamepad) -> Option<u8> {
+        if let Some(player_id) = self.player(gamepad) {
+            return Some(player_id);
+        }
+        let player_id = self.0.iter().position(Option::is_none)?;
+        self.0[player_id] = Some(gamepad);
+        Some(player_id as u8)
+    }
+
+    pub fn release(&mut self, gamepad: Gamepad) -> Option<u8> {
+        let player_id = self.player(gamepad)?;
+        self.0[usize::from(player_id)] = None;
+        Some(player_id)
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    #[must_use]
+    pub fn player(&self, gamepad: Gamepad) -> Option<u8> {
+        self.0
+            .iter()
+            .position(|assigned| *assigned == Some(gamepad))
+            .map(|player_id| player_id as u8)
+    }
+}
+
+/// How far the left stick has to be pushed before it counts as a direction.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Deref)]
+pub struct StickDeadzone(pub f32);
+
+impl Default for StickDeadzone {
+    fn default() -> Self {
+        Self(0.5)
+    }
+}
+
+/// The direction held on `gamepad`: the d-pad if pressed, otherwise the left stick's stronger axis.
+#[must_use]
+pub fn held_direction(
+    gamepad: Gamepad,
+    buttons: &Input<GamepadButton>,
+    axes: &Axis<GamepadAxis>,
+    deadzone: f32,
+) -> Option<Direction> {
+    let dpad = [
+        (GamepadButtonType::DPadLeft, Direction::Left),
+        (GamepadButtonType::DPadDown, Direction::Down),
+        (GamepadButtonType::DPadUp, Direction::Up),
+        (GamepadButtonType::DPadRight, Direction::Right),
+    ]
+    .into_iter()
+    .find(|(button, _)| buttons.pressed(GamepadButton::new(gamepad, *button)))
+    .map(|(_, direction)| direction);
+    if dpad.is_some() {
+        return dpad;
+    }
+
+    let axis = |axis_type| {
+        axes.get(GamepadAxis::new(gamepad, axis_type))
+            .unwrap_or_default()
+    };
+    let (x, y) = (
+        axis(GamepadAxisType::LeftStickX),
+        axis(GamepadAxisType::LeftStickY),
+    );
+    if x.abs().max(y.abs()) < deadzone {
+        None
+    } else if x.abs() >= y.abs() {
+        Some(if x < 0. {
+            Direction::Left
+        } else {
+            Direction::Right
+        })
+    } else {
+        Some(if y < 0. {
+            Direction::Down
+        } else {
+            Direction::Up
+        })
+    }
+}
+
+pub fn gamepad_connection_system(
+    mut events: EventReader<GamepadConnectionEvent>,
+    mut assignments: ResMut<GamepadAssignments>,
+) {
+    for event in events.iter() {
+        match event.connection {
+            GamepadConnection::Connected(_) => {
+                if let Some(player_id) = assignments.assign(event.gamepad) {
+                    println!(
+                        "Gamepad {} controls player {}",
+                        event.gamepad.id,
+                        player_id + 1
+                    );
+                }
+            }
+            GamepadConnection::Disconnected => {
+                assignments.release(event.gamepad);
+            }
+        }
+    }
+}
+
+/// Steers with whichever pad is assigned to the player. Like held keys, a held direction keeps
+/// offering its turn every frame and `TurnQueue` drops the repeats.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GamepadController {
+    turns: TurnQueue,
+}
+
+impl SnakeController for GamepadController {
+    fn next_direction(&mut self, _view: BoardView) -> Option<Direction> {
+        self.turns.pop()
+    }
+
+    fn read_input(&mut self, view: BoardView, input: &mut ControlInput) {
+        let (Some(gamepads), Some(buttons), Some(axes)) =
+            (&input.gamepads, &input.gamepad_buttons, &input.gamepad_axes)
+        else {
+            return;
+        };
+        let deadzone = input.deadzone.as_deref().copied().unwrap_or_default();
+        if let Some(direction) = gamepads
+            .get(usize::from(view.player_id()))
+            .copied()
+            .flatten()
+            .and_then(|gamepad| held_direction(gamepad, buttons, axes, *deadzone))
+        {
+            self.turns.push(view.snake().direction, direction);
+        }
+    }
+
+    fn restart(&mut self) {
+        self.turns.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::input::gamepad::GamepadInfo;
+
+    use super::*;
+    use crate::{
+        components::{GameEndEvent, Player, Position},
+        controller::{self, Controllers},
+        simulation::GameState,
+        snake::{self, Head, PlayerConfigs, Segments},
+    };
+
+    fn connected(gamepad: Gamepad) -> GamepadConnectionEvent {
+        GamepadConnectionEvent::new(
+            gamepad,
+            GamepadConnection::Connected(GamepadInfo {
+                name: "Test pad".to_string(),
+            }),
+        )
+    }
+
+    fn gamepad_app() -> App {
+        let mut app = App::new();
+        app.insert_resource(Segments::default())
+            .insert_resource(GameState::default())
+            .init_resource::<PlayerConfigs>()
+            .insert_resource(GamepadAssignments::new(2))
+            .init_resource::<StickDeadzone>()
+            .init_resource::<Input<GamepadButton>>()
+            .init_resource::<Axis<GamepadAxis>>()
+            .add_event::<GamepadConnectionEvent>()
+            .add_event::<GameEndEvent>()
+            .add_startup_system(snake::spawn_system)
+            .insert_resource(
+                Controllers::new(2)
+                    .with(0, GamepadController::default())
+                    .with(1, GamepadController::default()),
+            )
+            .add_systems(
+                (
+                    gamepad_connection_system,
+                    controller::controller_input_system,
+                    controller::controller_system,
+                    snake::movement_system,
+                )
+                    .chain(),
+            );
+        app
+    }
+
+    fn head_position(app: &mut App, player_id: u8) -> Position {
+        let mut query = app.world.query::<(&Position, &Player, With<Head>)>();
+        query
+            .iter(&app.world)
+            .find(|(_, player, _)| player.id == player_id)
+            .map(|(position, _, _)| position.clone())
+            .unwrap()
+    }
+
+    #[test]
+    fn pads_are_assigned_in_connection_order_and_freed_on_disconnect() {
+        let mut assignments = GamepadAssignments::new(2);
+
+        assert_eq!(assignments.assign(Gamepad::new(4)), Some(0));
+        assert_eq!(assignments.assign(Gamepad::new(1)), Some(1));
+        assert_eq!(assignments.assign(Gamepad::new(2)), None);
+        assert_eq!(assignments.assign(Gamepad::new(4)), Some(0));
+
+        assert_eq!(assignments.release(Gamepad::new(4)), Some(0));
+        assert_eq!(assignments.assign(Gamepad::new(2)), Some(0));
+    }
+
+    #[test]
+    fn stick_respects_the_deadzone() {
+        let gamepad = Gamepad::new(0);
+        let buttons = Input::<GamepadButton>::default();
+        let mut axes = Axis::<GamepadAxis>::default();
+
+        axes.set(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX), -0.3);
+        assert_eq!(held_direction(gamepad, &buttons, &axes, 0.5), None);
+
+        axes.set(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX), -0.7);
+        axes.set(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY), 0.6);
+        assert_eq!(
+            held_direction(gamepad, &buttons, &axes, 0.5),
+            Some(Direction::Left)
+        );
+    }
+
+    #[test]
+    fn hot_plugged_pad_steers_with_the_dpad() {
+        let mut app = gamepad_app();
+        let gamepad = Gamepad::new(3);
+        app.world.send_event(connected(gamepad));
+
+        let mut buttons = Input::<GamepadButton>::default();
+        buttons.press(GamepadButton::new(gamepad, GamepadButtonType::DPadRight));
+        app.insert_resource(buttons);
+        app.update();
+
+        assert_eq!(head_position(&mut app, 0), Position { x: 4, y: 3 });
+        assert_eq!(head_position(&mut app, 1), Position { x: 17, y: 4 });
+    }
+
+    #[test]
+    fn stick_steers_once_pushed_past_the_deadzone() {
+        let mut app = gamepad_app();
+        let gamepad = Gamepad::new(0);
+        app.world.send_event(connected(gamepad));
+
+        let mut axes = Axis::<GamepadAxis>::default();
+        axes.set(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX), 0.9);
+        app.insert_resource(axes);
+        app.update();
+        app.update();
+
+        let mut query = app.world.query::<(&Head, &Player)>();
+        let (head, _) = query
+            .iter(&app.world)
+            .find(|(_, player)| player.id == 0)
+            .unwrap();
+        assert_eq!(head.direction(), Direction::Right);
+        assert_eq!(head_position(&mut app, 0), Position { x: 5, y: 3 });
+    }
+
+    #[test]
+    fn held_directions_are_queued_once_there_is_room() {
+        let gamepad = Gamepad::new(0);
+        let mut app = App::new();
+        app.insert_resource(GameState::default())
+            .insert_resource(GamepadAssignments::new(1))
+            .init_resource::<StickDeadzone>()
+            .init_resource::<Axis<GamepadAxis>>()
+            .insert_resource(Controllers::new(1).with(0, GamepadController::default()))
+            .add_event::<GamepadConnectionEvent>()
+            .add_systems(
+                (
+                    gamepad_connection_system,
+                    controller::controller_input_system,
+                )
+                    .chain(),
+            );
+        app.world.send_event(connected(gamepad));
+        let hold = |app: &mut App, button| {
+            let mut buttons = Input::<GamepadButton>::default();
+            buttons.press(GamepadButton::new(gamepad, button));
+            app.insert_resource(buttons);
+            app.update();
+        };
+        let tick = |app: &mut App| {
+            let state = app.world.resource::<GameState>().clone();
+            app.world.resource_mut::<Controllers>().decide(&state)[0]
+        };
+
+        // The snake starts heading up. Two turns fill the queue, so the held left is dropped.
+        hold(&mut app, GamepadButtonType::DPadRight);
+        hold(&mut app, GamepadButtonType::DPadUp);
+        hold(&mut app, GamepadButtonType::DPadLeft);
+        assert_eq!(tick(&mut app), Some(Direction::Right));
+        hold(&mut app, GamepadButtonType::DPadLeft);
+
+        assert_eq!(tick(&mut app), Some(Direction::Up));
+        assert_eq!(tick(&mut app), Some(Direction::Left));
+    }
+}
@@ -1,44 +1,108 @@
 use crate::components::{Position, Size};
 use bevy::{prelude::*, window::PrimaryWindow};
+use serde::{Deserialize, Serialize};
 
-#[cfg(debug_assertions)]
-pub(crate) const GRID_WIDTH: u16 = 10;
-#[cfg(not(debug_assertions))]
-pub(crate) const GRID_WIDTH: u16 = 20;
-#[cfg(debug_assertions)]
-pub(crate) const GRID_HEIGHT: u16 = 10;
-#[cfg(not(debug_assertions))]
-pub(crate) const GRID_HEIGHT: u16 = 20;
+/// What happens to a head that moves past the edge of the board.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EdgePolicy {
+    #[default]
+    Walls,
+    Wrap,
+}
+
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BoardConfig {
+    pub width: u16,
+    pub height: u16,
+    pub edges: EdgePolicy,
+}
+
+impl Default for BoardConfig {
+    fn default() -> Self {
+        Self::new(20, 20)
+    }
+}
+
+impl BoardConfig {
+    #[must_use]
+    pub const fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            edges: EdgePolicy::Walls,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_edges(mut self, edges: EdgePolicy) -> Self {
+        self.edges = edges;
+        self
+    }
+
+    /// Moves a position that left the board back onto the opposite edge when wrapping.
+    #[allow(clippy::cast_possible_wrap)]
+    #[must_use]
+    pub const fn wrap(&self, position: Position) -> Position {
+        match self.edges {
+            EdgePolicy::Walls => position,
+            EdgePolicy::Wrap => Position {
+                x: position.x.rem_euclid(self.width as i16),
+                y: position.y.rem_euclid(self.height as i16),
+            },
+        }
+    }
+
+    #[must_use]
+    pub const fn cells(&self) -> u32 {
+        self.width as u32 * self.height as u32
+    }
+
+    #[allow(clippy::cast_sign_loss)]
+    #[must_use]
+    pub const fn contains(&self, position: &Position) -> bool {
+        position.x >= 0
+            && position.y >= 0
+            && (position.x as u16) < self.width
+            && (position.y as u16) < self.height
+    }
+}
 
 #[allow(clippy::missing_panics_doc)]
 #[allow(clippy::needless_pass_by_value)]
 pub fn size_scaling(
+    board: Res<BoardConfig>,
     primary_window: Query<&Window, With<PrimaryWindow>>,
     mut q: Query<(&Size, &mut Transform)>,
 ) {
     let window = primary_window.get_single().unwrap();
     for (sprite_size, mut transform) in q.iter_mut() {
-        scale_sprite(transform.as_mut(), sprite_size, window);
+        scale_sprite(transform.as_mut(), sprite_size, window, &board);
     }
 }
 
 #[allow(clippy::missing_panics_doc)]
 #[allow(clippy::needless_pass_by_value)]
 pub fn position_translation(
+    board: Res<BoardConfig>,
     primary_window: Query<&Window, With<PrimaryWindow>>,
     mut q: Query<(&Position, &mut Transform)>,
 ) {
     let window = primary_window.get_single().unwrap();
     for (pos, mut transform) in q.iter_mut() {
-        translate_position(transform.as_mut(), pos, window);
+        translate_position(transform.as_mut(), pos, window, &board);
     }
 }
 
 #[allow(clippy::cast_lossless)]
-fn scale_sprite(transform: &mut Transform, sprite_size: &Size, window: &Window) {
+fn scale_sprite(
+    transform: &mut Transform,
+    sprite_size: &Size,
+    window: &Window,
+    board: &BoardConfig,
+) {
     transform.scale = Vec3::new(
-        sprite_size.width / GRID_WIDTH as f32 * window.width(),
-        sprite_size.height / GRID_HEIGHT as f32 * window.height(),
+        sprite_size.width / board.width as f32 * window.width(),
+        sprite_size.height / board.height as f32 * window.height(),
         1.0,
     );
 }
@@ -50,10 +114,15 @@ fn convert(pos: f32, bound_window: f32, grid_side_lenght: f32) -> f32 {
 }
 
 #[allow(clippy::cast_lossless)]
-fn translate_position(transform: &mut Transform, pos: &Position, window: &Window) {
+fn translate_position(
+    transform: &mut Transform,
+    pos: &Position,
+    window: &Window,
+    board: &BoardConfig,
+) {
     transform.translation = Vec3::new(
-        convert(pos.x as f32, window.width(), GRID_WIDTH as f32),
-        convert(pos.y as f32, window.height(), GRID_HEIGHT as f32),
+        convert(pos.x as f32, window.width(), board.width as f32),
+        convert(pos.y as f32, window.height(), board.height as f32),
         0.0,
     );
 }
@@ -68,12 +137,6 @@ mod test {
     #[test]
     fn transform_has_correct_scale_for_window() {
         // Setup
-        #[cfg(debug_assertions)]
-        let expected_transform = Transform {
-            scale: Vec3::new(20., 20., 1.),
-            ..default()
-        };
-        #[cfg(not(debug_assertions))]
         let expected_transform = Transform {
             scale: Vec3::new(10., 10., 1.),
             ..default()
@@ -92,28 +155,91 @@ mod test {
         };
 
         // Apply scale
-        scale_sprite(&mut default_transform, &sprite_size, &window);
+        scale_sprite(
+            &mut default_transform,
+            &sprite_size,
+            &window,
+            &BoardConfig::default(),
+        );
 
         assert_eq!(default_transform, expected_transform);
     }
 
+    #[test]
+    fn transform_has_correct_scale_for_non_square_board() {
+        let mut transform = Transform::default();
+        let window = Window {
+            resolution: WindowResolution::new(200., 200.),
+            ..default()
+        };
+
+        scale_sprite(
+            &mut transform,
+            &Size::square(1.),
+            &window,
+            &BoardConfig::new(10, 40),
+        );
+
+        assert_eq!(transform.scale, Vec3::new(20., 5., 1.));
+    }
+
+    #[test]
+    fn board_contains_only_cells_inside_bounds() {
+        let board = BoardConfig::new(4, 2);
+
+        assert!(board.contains(&Position { x: 0, y: 0 }));
+        assert!(board.contains(&Position { x: 3, y: 1 }));
+        assert!(!board.contains(&Position { x: 4, y: 1 }));
+        assert!(!board.contains(&Position { x: 3, y: 2 }));
+        assert!(!board.contains(&Position { x: -1, y: 0 }));
+        assert_eq!(board.cells(), 8);
+    }
+
+    #[test]
+    fn wrap_moves_positions_to_the_opposite_edge() {
+        let walls = BoardConfig::new(4, 2);
+        let wrap = walls.with_edges(EdgePolicy::Wrap);
+
+        assert_eq!(
+            walls.wrap(Position { x: -1, y: 2 }),
+            Position { x: -1, y: 2 }
+        );
+        assert_eq!(wrap.wrap(Position { x: -1, y: 2 }), Position { x: 3, y: 0 });
+        assert_eq!(wrap.wrap(Position { x: 4, y: -1 }), Position { x: 0, y: 1 });
+        assert_eq!(wrap.wrap(Position { x: 2, y: 1 }), Position { x: 2, y: 1 });
+    }
+
+    #[test]
+    fn wrapped_position_is_translated_inside_the_window() {
+        let board = BoardConfig::new(10, 10).with_edges(EdgePolicy::Wrap);
+        let window = Window {
+            resolution: WindowResolution::new(400., 400.),
+            ..default()
+        };
+        let mut transform = Transform::default();
+
+        translate_position(
+            &mut transform,
+            &board.wrap(Position { x: -1, y: 10 }),
+            &window,
+            &board,
+        );
+
+        assert_relative_eq!(transform.translation.x, 180., epsilon = 0.0001);
+        assert_relative_eq!(transform.translation.y, -180., epsilon = 0.0001);
+    }
+
     #[test]
     fn convert_position_x_for_grid_width() {
-        let x = convert(4., 400., GRID_WIDTH as f32);
+        let x = convert(4., 400., BoardConfig::default().width as f32);
 
-        #[cfg(debug_assertions)]
-        assert_relative_eq!(x, -20., epsilon = 0.00001);
-        #[cfg(not(debug_assertions))]
         assert_relative_eq!(x, -110., epsilon = 0.00001);
     }
 
     #[test]
     fn convert_position_y_for_grid_height() {
-        let x = convert(5., 400., GRID_HEIGHT as f32);
+        let x = convert(5., 400., BoardConfig::default().height as f32);
 
-        #[cfg(debug_assertions)]
-        assert_relative_eq!(x, 20., epsilon = 0.00001);
-        #[cfg(not(debug_assertions))]
         assert_relative_eq!(x, -90., epsilon = 0.00001)
     }
 
@@ -122,9 +248,6 @@ mod test {
         let position = Position { x: 2, y: 8 };
         let mut default_transform = Transform::default();
         let expected = Transform {
-            #[cfg(debug_assertions)]
-            translation: Vec3::new(-100., 140., 0.),
-            #[cfg(not(debug_assertions))]
             translation: Vec3::new(-150., -29.999996, 0.),
             ..default()
         };
@@ -141,7 +264,12 @@ mod test {
         };
 
         // Apply translation
-        translate_position(&mut default_transform, &position, &window);
+        translate_position(
+            &mut default_transform,
+            &position,
+            &window,
+            &BoardConfig::default(),
+        );
 
         assert_eq!(default_transform, expected);
     }
@@ -0,0 +1,232 @@
+use std::fmt;
+
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    components::GameEndEvent, controller::Controllers, rng::GameRng, simulation::GameState,
+};
+
+/// How many unattended matches to play, and when to call a match that never ends.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MatchLimits {
+    pub matches: u32,
+    /// Matches still running after this many ticks count as draws.
+    pub max_ticks: u64,
+}
+
+impl Default for MatchLimits {
+    fn default() -> Self {
+        Self {
+            matches: 100,
+            max_ticks: 10_000,
+        }
+    }
+}
+
+/// Results of every finished match, indexed by `Player::id`.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MatchStats {
+    pub games: u32,
+    pub wins: Vec<u32>,
+    /// Simultaneous deaths and matches cut off by `MatchLimits::max_ticks`.
+    pub draws: u32,
+    pub total_ticks: u64,
+    /// Sum of each player's final length.
+    pub total_lengths: Vec<u64>,
+}
+
+impl MatchStats {
+    #[must_use]
+    pub fn new(players: u8) -> Self {
+        Self {
+            wins: vec![0; usize::from(players)],
+            total_lengths: vec![0; usize::from(players)],
+            ..Self::default()
+        }
+    }
+
+    /// Records the finished match in `state`. A match without an outcome is a draw.
+    pub fn record(&mut self, state: &GameState) {
+        let players = state.snakes.len().max(self.wins.len());
+        self.wins.resize(players, 0);
+        self.total_lengths.resize(players, 0);
+
+        self.games += 1;
+        self.total_ticks += state.tick;
+        match &state.outcome {
+            Some(GameEndEvent::Winner(player)) => self.wins[player.id()] += 1,
+            Some(GameEndEvent::Draw) | None => self.draws += 1,
+            Some(GameEndEvent::AllDead) => (),
+        }
+        for (total, snake) in self.total_lengths.iter_mut().zip(&state.snakes) {
+            *total += snake.body.len() as u64;
+        }
+    }
+
+    /// Matches `player_id` neither won nor drew.
+    #[must_use]
+    pub fn losses(&self, player_id: usize) -> u32 {
+        self.games - self.wins.get(player_id).copied().unwrap_or(0) - self.draws
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn average_ticks(&self) -> f64 {
+        self.total_ticks as f64 / f64::from(self.games.max(1))
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn average_length(&self, player_id: usize) -> f64 {
+        self.total_lengths.get(player_id).copied().unwrap_or(0) as f64
+            / f64::from(self.games.max(1))
+    }
+}
+
+impl fmt::Display for MatchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} matches, {} draws, {:.1} ticks on average",
+            self.games,
+            self.draws,
+            self.average_ticks()
+        )?;
+        for (player_id, wins) in self.wins.iter().enumerate() {
+            writeln!(
+                f,
+                "P{} {wins:>5} W {:>5} L {:>5} D  avg len {:.1}",
+                player_id + 1,
+                self.losses(player_id),
+                self.draws,
+                self.average_length(player_id)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Plays one tick of the current match with the core rules only, so no entities or sprites are
+/// needed. Finished matches are recorded and restarted until `MatchLimits::matches` are done.
+#[allow(clippy::needless_pass_by_value)]
+pub fn headless_match_system(
+    limits: Res<MatchLimits>,
+    mut state: ResMut<GameState>,
+    mut rng: ResMut<GameRng>,
+    mut controllers: ResMut<Controllers>,
+    mut stats: ResMut<MatchStats>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if stats.games >= limits.matches {
+        return;
+    }
+    let inputs = controllers.decide(&state);
+    state.step(&inputs);
+    state.spawn_due_food(rng.as_mut());
+
+    if state.outcome.is_some() || state.tick >= limits.max_ticks {
+        stats.record(&state);
+        state.restart();
+        controllers.restart();
+        if stats.games >= limits.matches {
+            print!("{}", *stats);
+            exit.send(AppExit);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        bot::{BotController, BotLevel},
+        components::Player,
+        grid::BoardConfig,
+    };
+
+    fn headless_app(state: GameState, controllers: Controllers, limits: MatchLimits) -> App {
+        let mut app = App::new();
+        app.insert_resource(MatchStats::new(2))
+            .insert_resource(state)
+            .insert_resource(GameRng::new(4))
+            .insert_resource(controllers)
+            .insert_resource(limits)
+            .add_event::<AppExit>()
+            .add_system(headless_match_system);
+        app
+    }
+
+    fn run(app: &mut App) {
+        for _ in 0..1_000_000 {
+            app.update();
+            if !app.world.resource::<Events<AppExit>>().is_empty() {
+                return;
+            }
+        }
+        panic!("the matches never finished");
+    }
+
+    #[test]
+    fn stats_count_wins_draws_and_losses() {
+        let mut stats = MatchStats::new(2);
+        stats.record(&GameState {
+            tick: 10,
+            outcome: Some(GameEndEvent::Winner(Player { id: 1 })),
+            ..GameState::default()
+        });
+        stats.record(&GameState {
+            tick: 20,
+            outcome: Some(GameEndEvent::Draw),
+            ..GameState::default()
+        });
+
+        assert_eq!(stats.games, 2);
+        assert_eq!(stats.wins, vec![0, 1]);
+        assert_eq!((stats.losses(0), stats.losses(1)), (1, 0));
+        assert_eq!(stats.draws, 1);
+        approx::assert_relative_eq!(stats.average_ticks(), 15.0);
+        approx::assert_relative_eq!(stats.average_length(0), 2.0);
+    }
+
+    #[test]
+    fn endless_matches_are_cut_off_as_draws() {
+        // Nobody steers, so both snakes head for the top wall.
+        let state = GameState::new(BoardConfig::new(12, 12), 2);
+        let mut app = headless_app(
+            state,
+            Controllers::new(2),
+            MatchLimits {
+                matches: 1,
+                max_ticks: 3,
+            },
+        );
+
+        run(&mut app);
+
+        let stats = app.world.resource::<MatchStats>();
+        assert_eq!(stats.games, 1);
+        assert_eq!(stats.draws, 1);
+        assert_eq!(stats.total_ticks, 3);
+    }
+
+    #[test]
+    fn bots_play_the_requested_number_of_matches() {
+        let state = GameState::new(BoardConfig::new(12, 12), 2);
+        let bots = Controllers::new(2)
+            .with(0, BotController(BotLevel::LookAhead))
+            .with(1, BotController(BotLevel::Greedy));
+        let limits = MatchLimits {
+            matches: 5,
+            max_ticks: 500,
+        };
+        let mut app = headless_app(state, bots, limits);
+
+        run(&mut app);
+
+        let stats = app.world.resource::<MatchStats>();
+        assert_eq!(stats.games, 5);
+        assert!(stats.wins.iter().sum::<u32>() + stats.draws <= 5);
+        assert!(stats.total_ticks > 0);
+        assert_eq!(app.world.resource::<GameState>().tick, 0);
+    }
+}
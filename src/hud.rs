@@ -0,0 +1,184 @@
+use bevy::prelude::*;
+
+use crate::{
+    game::GameResult,
+    score::{PlayerScore, Scoreboard},
+    snake::PlayerConfigs,
+};
+
+const FONT: &str = "fonts/DejaVuSansMono.ttf";
+const FONT_SIZE: f32 = 20.;
+const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+
+/// Text showing the score of `player_id`.
+#[derive(Component)]
+pub struct HudText {
+    pub player_id: u8,
+}
+
+#[derive(Component)]
+pub struct GameOverScreen;
+
+#[must_use]
+pub fn score_line(player_id: u8, score: &PlayerScore) -> String {
+    format!(
+        "P{} {:>5} pts  len {:>3}",
+        player_id + 1,
+        score.points,
+        score.length
+    )
+}
+
+fn text_style(asset_server: &AssetServer) -> TextStyle {
+    TextStyle {
+        font: asset_server.load(FONT),
+        font_size: FONT_SIZE,
+        color: TEXT_COLOR,
+    }
+}
+
+/// One row per player in the top left corner: a color swatch and the score text.
+#[allow(clippy::needless_pass_by_value)]
+pub fn setup_hud_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    players: Res<PlayerConfigs>,
+) {
+    let style = text_style(&asset_server);
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.),
+                    top: Val::Px(10.),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                gap: Size::height(Val::Px(4.)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|hud| {
+            for (player_id, config) in (0..).zip(players.iter()) {
+                hud.spawn(NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::Center,
+                        gap: Size::width(Val::Px(8.)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn(NodeBundle {
+                        style: Style {
+                            size: Size::all(Val::Px(FONT_SIZE * 0.8)),
+                            ..default()
+                        },
+                        background_color: config.color.into(),
+                        ..default()
+                    });
+                    row.spawn((
+                        TextBundle::from_section(
+                            score_line(player_id, &PlayerScore::default()),
+                            style.clone(),
+                        ),
+                        HudText { player_id },
+                    ));
+                });
+            }
+        });
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn hud_system(scoreboard: Res<Scoreboard>, mut texts: Query<(&mut Text, &HudText)>) {
+    if !scoreboard.is_changed() {
+        return;
+    }
+    texts.for_each_mut(|(mut text, hud_text)| {
+        if let Some(score) = scoreboard.get(usize::from(hud_text.player_id)) {
+            text.sections[0].value = score_line(hud_text.player_id, score);
+        }
+    });
+}
+
+/// Outcome and final scores in the middle of the screen.
+#[allow(clippy::needless_pass_by_value)]
+pub fn game_over_screen_system(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    result: Res<GameResult>,
+    scoreboard: Res<Scoreboard>,
+) {
+    let mut lines = vec![result.outcome.clone().unwrap_or_default().to_string()];
+    lines.extend(
+        (0..)
+            .zip(scoreboard.iter())
+            .map(|(player_id, score)| score_line(player_id, score)),
+    );
+    lines.push("Press R to play again".to_string());
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::all(Val::Percent(100.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            GameOverScreen,
+        ))
+        .with_children(|screen| {
+            screen.spawn(
+                TextBundle::from_section(lines.join("\n"), text_style(&asset_server))
+                    .with_text_alignment(TextAlignment::Center),
+            );
+        });
+}
+
+pub fn clear_game_over_screen_system(
+    mut commands: Commands,
+    screens: Query<Entity, With<GameOverScreen>>,
+) {
+    screens.for_each(|entity| commands.entity(entity).despawn_recursive());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hud_text_follows_the_scoreboard() {
+        // Setup
+        let mut app = App::new();
+        app.insert_resource(Scoreboard(vec![
+            PlayerScore::default(),
+            PlayerScore {
+                points: 42,
+                food: 3,
+                length: 5,
+                ticks_alive: 12,
+            },
+        ]))
+        .add_system(hud_system);
+        let text = app
+            .world
+            .spawn((
+                Text::from_section("", TextStyle::default()),
+                HudText { player_id: 1 },
+            ))
+            .id();
+
+        // Run systems
+        app.update();
+
+        assert_eq!(
+            app.world.get::<Text>(text).unwrap().sections[0].value,
+            "P2    42 pts  len   5"
+        );
+    }
+}
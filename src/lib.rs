@@ -0,0 +1,23 @@
+pub mod battlesnake;
+pub mod bindings;
+pub mod bot;
+pub mod cli;
+pub mod components;
+pub mod controller;
+pub mod env;
+pub mod food;
+pub mod game;
+pub mod gamepad;
+pub mod grid;
+pub mod headless;
+pub mod hud;
+pub mod net;
+pub mod replay;
+pub mod rng;
+pub mod score;
+pub mod simulation;
+pub mod snake;
+pub mod snapshot;
+pub mod speed;
+pub mod tick;
+pub mod vec_env;
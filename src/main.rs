@@ -1,50 +1,281 @@
-use std::time::Duration;
+use bevy::{prelude::*, window::ExitCondition};
+use bevy_snake::{
+    battlesnake::BattlesnakeController,
+    bindings::{self, InputBindings, KeyboardController},
+    bot::{BotController, BotLevel},
+    cli::Args,
+    components::GameEndEvent,
+    controller::{self, CombinedController, Controllers},
+    food,
+    game::{self, AppState},
+    gamepad::{self, GamepadAssignments, GamepadController},
+    grid, hud,
+    net::{self, Lockstep, NetworkController},
+    replay::{self, Replay, ReplayPlayback, ReplayRecorder},
+    score::{self, Scoreboard},
+    simulation::GameState,
+    snake::{self, GrowthEvent},
+    snapshot::{self, SnapshotPath},
+    speed::{self, SpeedCurve, SpeedProgression},
+    tick::{self, GameTick, TickBoost},
+};
 
-use bevy::{prelude::*, time::common_conditions::on_timer, window::ExitCondition};
-use components::GameEndEvent;
-use snake::GrowthEvent;
+fn main() {
+    let args = Args::from_env();
+    let mut board = args.board();
+    let mut rng = args.rng();
+    println!("Seed: {}", rng.seed());
+    let lockstep = args.value("--net-peer").map(|peer| {
+        if !args.seeded() {
+            eprintln!("Network games need the same --seed or --daily on both sides");
+            std::process::exit(1);
+        }
+        let lockstep = peer
+            .parse()
+            .map_err(|error| format!("{peer}: {error}"))
+            .and_then(|peer| {
+                let local_player = args.value("--net-player").map_or(Ok(0), |player| {
+                    player
+                        .parse()
+                        .map_err(|error| format!("--net-player {player}: {error}"))
+                })?;
+                Lockstep::bind(
+                    args.value("--net-bind")
+                        .unwrap_or_else(|| "0.0.0.0:7777".to_string()),
+                    peer,
+                    local_player,
+                    args.parsed("--input-delay").unwrap_or(2),
+                )
+                .map_err(|error| error.to_string())
+            });
+        lockstep.unwrap_or_else(|error| {
+            eprintln!("{error}");
+            std::process::exit(1);
+        })
+    });
+    let playback = args.value("--replay").map(|path| {
+        Replay::load(&path).unwrap_or_else(|error| {
+            eprintln!("{path}: {error}");
+            std::process::exit(1);
+        })
+    });
+    #[allow(clippy::cast_possible_truncation)]
+    let players = match (&playback, &lockstep) {
+        (Some(replay), _) => replay.spawn_points.len() as u8,
+        (None, Some(_)) => 2,
+        (None, None) => args.parsed("--players").unwrap_or(2),
+    };
+    let food_config = args.food();
+    let speed = SpeedProgression::default().with_curve(
+        args.value("--speed")
+            .and_then(|curve| SpeedCurve::from_name(&curve))
+            .unwrap_or_default(),
+    );
 
-pub mod components;
-pub mod food;
-pub mod game;
-pub mod grid;
-pub mod snake;
+    let mut bindings = args.value("--bindings").map_or_else(
+        || InputBindings::new(players),
+        |path| {
+            InputBindings::load(&path).unwrap_or_else(|error| {
+                eprintln!("{path}: {error}, using default controls");
+                InputBindings::new(players)
+            })
+        },
+    );
+    // Everyone steers with their keys and pad. Network games steer both snakes with the inputs
+    // the peers agree on, and have no bots.
+    let mut controllers = Controllers::new(players);
+    for player_id in 0..players {
+        controllers.set(player_id, local_controller(&bindings, player_id));
+    }
+    if let Some(lockstep) = &lockstep {
+        let local_player = lockstep.local_player();
+        controllers.set(
+            local_player,
+            NetworkController::local(local_controller(&bindings, local_player)),
+        );
+        controllers.set(1 - local_player, NetworkController::remote());
+    }
+    // Every player after the first is a bot at the given level.
+    if let Some(level) = args
+        .value("--bot")
+        .and_then(|level| BotLevel::from_name(&level))
+        .filter(|_| lockstep.is_none())
+    {
+        for player_id in 1..players {
+            controllers.set(player_id, BotController(level));
+        }
+    }
+    // Comma-separated Battlesnake servers, one per player from the first.
+    if let Some(urls) = args.value("--battlesnake").filter(|_| lockstep.is_none()) {
+        for (player_id, url) in (0..players).zip(urls.split(',')) {
+            controllers.set(player_id, BattlesnakeController::new(url));
+        }
+    }
+    let mut state = GameState::new(board, players).with_food(food_config);
+    if let (None, Err(error)) = (&playback, state.check_spawns()) {
+        eprintln!("--players {players}: {error}");
+        std::process::exit(1);
+    }
+    // Replays drive every snake from the recording, so local controls are switched off.
+    if let Some(replay) = &playback {
+        board = replay.board;
+        rng = replay.rng.clone();
+        state = replay.initial_state();
+        controllers = replay.controllers();
+        bindings.players.clear();
+    }
+    let gamepads = if playback.is_some() {
+        GamepadAssignments::default()
+    } else {
+        GamepadAssignments::new(players)
+    };
 
-fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                resolution: (1000., 1000.).into(),
-                title: "Snake Game".to_string(),
-                ..default()
-            }),
-            exit_condition: ExitCondition::OnAllClosed,
-            close_when_requested: true,
-        }))
-        .insert_resource(snake::Segments::default())
-        .insert_resource(snake::LastTailPosition::default())
-        .add_event::<GrowthEvent>()
-        .add_event::<GameEndEvent>()
-        .add_startup_system(setup_camera)
-        .add_startup_system(snake::spawn_system)
-        .add_system(food::spawn_system.run_if(on_timer(Duration::from_secs_f32(1.0))))
-        .add_system(snake::movement_system.run_if(on_timer(Duration::from_secs_f32(0.15))))
-        .add_system(
-            snake::eating_system
-                .after(snake::movement_system)
-                .run_if(on_timer(Duration::from_secs_f32(0.15))),
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins.set(WindowPlugin {
+        primary_window: Some(Window {
+            resolution: (1000., 1000.).into(),
+            title: "Snake Game".to_string(),
+            ..default()
+        }),
+        exit_condition: ExitCondition::OnAllClosed,
+        close_when_requested: true,
+    }))
+    .insert_resource(snake::Segments::default())
+    .insert_resource(board)
+    .insert_resource(Scoreboard::from_state(&state))
+    .insert_resource(state)
+    .init_resource::<score::ScoreRules>()
+    .insert_resource(snake::PlayerConfigs::new(players))
+    .insert_resource(bindings)
+    .insert_resource(controllers)
+    .init_resource::<TickBoost>()
+    .insert_resource(gamepads)
+    .init_resource::<gamepad::StickDeadzone>()
+    .insert_resource(rng)
+    .insert_resource(game::GameResult::default())
+    .init_resource::<game::Countdown>()
+    .init_resource::<game::MatchScore>()
+    .insert_resource(speed)
+    .insert_resource(speed.timer())
+    .add_state::<AppState>()
+    .add_event::<GrowthEvent>()
+    .add_event::<GameEndEvent>()
+    .add_startup_system(setup_camera)
+    .add_startup_system(snake::spawn_system)
+    .add_startup_system(hud::setup_hud_system)
+    .add_system(hud::hud_system)
+    .add_system(game::main_menu_system.in_schedule(OnEnter(AppState::MainMenu)))
+    .add_system(game::menu_input_system.in_set(OnUpdate(AppState::MainMenu)))
+    .add_system(game::start_countdown_system.in_schedule(OnEnter(AppState::Countdown)))
+    .add_system(game::countdown_system.in_set(OnUpdate(AppState::Countdown)))
+    .add_system(game::pause_input_system)
+    .add_system(gamepad::gamepad_connection_system)
+    .add_systems(
+        (game::record_match_system, hud::game_over_screen_system)
+            .in_schedule(OnEnter(AppState::GameOver)),
+    )
+    .add_system(game::rematch_input_system.in_set(OnUpdate(AppState::GameOver)))
+    .add_systems(
+        (
+            game::reset_game_system,
+            score::reset_system,
+            controller::restart_system,
+            snake::spawn_system,
+            hud::clear_game_over_screen_system,
+        )
+            .chain()
+            .in_schedule(OnExit(AppState::GameOver)),
+    )
+    .add_systems(
+        (
+            speed::speed_progression_system.before(tick::tick_timer_system),
+            bindings::boost_input_system.before(tick::tick_timer_system),
+            tick::tick_timer_system,
+            controller::controller_input_system
+                .after(tick::tick_timer_system)
+                .after(gamepad::gamepad_connection_system),
         )
-        .add_system(
-            snake::growth_system
-                .after(snake::eating_system)
-                .run_if(on_timer(Duration::from_secs_f32(0.15))),
+            .before(GameTick)
+            .in_set(OnUpdate(AppState::Playing)),
+    )
+    .add_systems(
+        (
+            controller::controller_system,
+            snake::movement_system,
+            snake::eating_system,
+            snake::growth_system,
+            score::scoreboard_system,
+            food::spawn_system,
+            game::game_over_system,
         )
-        .add_system(snake::movement_input_system.before(snake::movement_system))
-        .add_system(game::game_over_system.after(snake::movement_system))
-        .add_systems(
-            (grid::position_translation, grid::size_scaling).in_base_set(CoreSet::PostUpdate),
+            .chain()
+            .in_set(GameTick),
+    )
+    .add_systems((grid::position_translation, grid::size_scaling).in_base_set(CoreSet::PostUpdate));
+
+    // Loading a game on one side only would desync a network game.
+    if lockstep.is_none() {
+        app.insert_resource(
+            args.value("--snapshot")
+                .map_or_else(SnapshotPath::default, SnapshotPath),
         )
-        .run();
+        .add_systems((
+            snapshot::save_snapshot_system,
+            snapshot::load_snapshot_system.after(GameTick),
+        ));
+    }
+    // Network games tick when both players' inputs are in, paced by each side's timer.
+    if let Some(lockstep) = lockstep {
+        app.insert_resource(lockstep)
+            .configure_set(
+                GameTick
+                    .in_set(OnUpdate(AppState::Playing))
+                    .run_if(net::tick_ready),
+            )
+            .add_systems(
+                (
+                    net::receive_system.before(controller::controller_input_system),
+                    net::send_system.after(controller::controller_input_system),
+                )
+                    .before(GameTick)
+                    .in_set(OnUpdate(AppState::Playing)),
+            )
+            .add_system(
+                net::checksum_system
+                    .before(controller::controller_system)
+                    .in_set(GameTick),
+            )
+            .add_system(net::reset_system.in_schedule(OnExit(AppState::GameOver)));
+    } else {
+        app.configure_set(
+            GameTick
+                .in_set(OnUpdate(AppState::Playing))
+                .run_if(tick::tick_finished),
+        );
+    }
+    if let Some(path) = args.value("--record") {
+        app.insert_resource(ReplayRecorder {
+            replay: None,
+            path: Some(path),
+        })
+        .add_system(replay::start_recording_system.in_schedule(OnEnter(AppState::Countdown)))
+        .add_system(replay::finish_recording_system.in_schedule(OnEnter(AppState::GameOver)));
+    }
+    if let Some(replay) = playback {
+        app.insert_resource(ReplayPlayback(replay))
+            .add_system(replay::verify_playback_system.in_schedule(OnEnter(AppState::GameOver)));
+    }
+    app.run();
+}
+
+/// A player's keys, if they have any, and whichever pad gets assigned to them.
+fn local_controller(bindings: &InputBindings, player_id: u8) -> CombinedController {
+    let controller = CombinedController::default();
+    let controller = match bindings.players.get(usize::from(player_id)) {
+        Some(keys) => controller.with(KeyboardController::new(*keys)),
+        None => controller,
+    };
+    controller.with(GamepadController::default())
 }
 
 fn setup_camera(mut commands: Commands) {
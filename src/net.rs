@@ -0,0 +1,533 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::Direction,
+    controller::{BoardView, ControlInput, SnakeController},
+    simulation::GameState,
+};
+
+/// How many of the latest local inputs every packet repeats, so a lost packet costs nothing.
+const RESEND_WINDOW: usize = 32;
+/// How many of the latest local checksums every packet carries.
+const CHECKSUM_WINDOW: usize = 8;
+/// Local checksums older than this many ticks are dropped unchecked.
+const CHECKSUM_HISTORY: u64 = 64;
+const MAX_PACKET_SIZE: usize = 4096;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Packet {
+    /// Games played so far, so inputs from a finished game never reach the rematch.
+    round: u32,
+    player_id: u8,
+    /// Tick of `inputs[0]`.
+    first_tick: u64,
+    inputs: Vec<Option<Direction>>,
+    /// `(tick, checksum)` of the state at the start of recent ticks.
+    checksums: Vec<(u64, u64)>,
+}
+
+/// Two-player lockstep over UDP. Each peer sends its own inputs and both simulate a tick only
+/// once they know both inputs for it.
+///
+/// Local inputs apply `delay` ticks after they are made, which hides the round trip as long as
+/// it is shorter than `delay` ticks. The first `delay` ticks have no inputs.
+#[derive(Resource, Debug)]
+pub struct Lockstep {
+    socket: UdpSocket,
+    peer: SocketAddr,
+    local_player: u8,
+    delay: u64,
+    round: u32,
+    /// Local inputs indexed by tick, including the empty ones before `delay`.
+    local: Vec<Option<Direction>>,
+    remote: BTreeMap<u64, Option<Direction>>,
+    checksums: BTreeMap<u64, u64>,
+    remote_checksums: BTreeMap<u64, u64>,
+    desync: Option<u64>,
+}
+
+impl Lockstep {
+    /// Listens on `address` and plays against `peer`. `local_player` is 0 or 1.
+    pub fn bind(
+        address: impl ToSocketAddrs,
+        peer: SocketAddr,
+        local_player: u8,
+        delay: u64,
+    ) -> io::Result<Self> {
+        Self::new(UdpSocket::bind(address)?, peer, local_player, delay)
+    }
+
+    /// Fails with `InvalidInput` unless `local_player` is 0 or 1.
+    pub fn new(
+        socket: UdpSocket,
+        peer: SocketAddr,
+        local_player: u8,
+        delay: u64,
+    ) -> io::Result<Self> {
+        if local_player > 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("local player must be 0 or 1, not {local_player}"),
+            ));
+        }
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            peer,
+            local_player,
+            delay,
+            round: 0,
+            local: vec![None; empty_ticks(delay)],
+            remote: BTreeMap::new(),
+            checksums: BTreeMap::new(),
+            remote_checksums: BTreeMap::new(),
+            desync: None,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    #[must_use]
+    pub const fn local_player(&self) -> u8 {
+        self.local_player
+    }
+
+    const fn remote_player(&self) -> u8 {
+        1 - self.local_player
+    }
+
+    /// The first tick whose checksums differed between the peers, if any.
+    #[must_use]
+    pub const fn desync(&self) -> Option<u64> {
+        self.desync
+    }
+
+    /// Whether the next local input is due while the game is at `tick`. Inputs are never made
+    /// more than `delay` ticks ahead, so a stalled peer stalls input too.
+    #[must_use]
+    pub const fn needs_input(&self, tick: u64) -> bool {
+        self.scheduled() <= tick + self.delay
+    }
+
+    /// Adds the next local input, for tick `scheduled()`.
+    pub fn schedule(&mut self, input: Option<Direction>) {
+        self.local.push(input);
+    }
+
+    /// The tick the next local input will apply to.
+    #[must_use]
+    pub const fn scheduled(&self) -> u64 {
+        self.local.len() as u64
+    }
+
+    /// Whether `tick` can be simulated: its local input was made a full `delay` ticks ago and
+    /// the peer's input for it has arrived.
+    #[must_use]
+    pub fn ready(&self, tick: u64) -> bool {
+        tick + self.delay < self.scheduled()
+            && (tick < self.delay || self.remote.contains_key(&tick))
+    }
+
+    /// Both players' inputs for `tick`, indexed by player id, once it is `ready`.
+    #[must_use]
+    pub fn inputs(&self, tick: u64) -> Option<Vec<Option<Direction>>> {
+        self.ready(tick)
+            .then(|| vec![self.input(0, tick), self.input(1, tick)])
+    }
+
+    /// `player_id`'s input for `tick` as far as it is known, which is `None` until it is.
+    #[must_use]
+    pub fn input(&self, player_id: u8, tick: u64) -> Option<Direction> {
+        if player_id == self.local_player {
+            self.local
+                .get(usize::try_from(tick).ok()?)
+                .copied()
+                .flatten()
+        } else {
+            self.remote.get(&tick).copied().flatten()
+        }
+    }
+
+    /// Remembers the checksum of the state at the start of `tick` and compares it with the peer's.
+    pub fn record_checksum(&mut self, tick: u64, checksum: u64) {
+        self.checksums.insert(tick, checksum);
+        self.checksums = self
+            .checksums
+            .split_off(&tick.saturating_sub(CHECKSUM_HISTORY));
+        self.compare_checksum(tick);
+    }
+
+    fn compare_checksum(&mut self, tick: u64) {
+        if let (Some(local), Some(remote)) = (
+            self.checksums.get(&tick),
+            self.remote_checksums.remove(&tick),
+        ) {
+            if *local != remote && self.desync.is_none() {
+                self.desync = Some(tick);
+            }
+        }
+    }
+
+    /// Forgets the finished game's inputs, ready for a rematch from tick 0.
+    pub fn reset(&mut self) {
+        self.round += 1;
+        self.local = vec![None; empty_ticks(self.delay)];
+        self.remote.clear();
+        self.checksums.clear();
+        self.remote_checksums.clear();
+        self.desync = None;
+    }
+
+    /// Sends the latest local inputs and checksums to the peer.
+    pub fn send(&self) -> io::Result<()> {
+        let first = self.local.len().saturating_sub(RESEND_WINDOW);
+        let packet = Packet {
+            round: self.round,
+            player_id: self.local_player,
+            first_tick: first as u64,
+            inputs: self.local[first..].to_vec(),
+            checksums: self
+                .checksums
+                .iter()
+                .rev()
+                .take(CHECKSUM_WINDOW)
+                .map(|(tick, checksum)| (*tick, *checksum))
+                .collect(),
+        };
+        let bytes = ron::to_string(&packet)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        self.socket.send_to(bytes.as_bytes(), self.peer)?;
+        Ok(())
+    }
+
+    /// Takes in everything the peer has sent so far. Packets from anyone else, from another
+    /// round or that do not parse are ignored.
+    pub fn receive(&mut self) -> io::Result<()> {
+        let mut buffer = [0; MAX_PACKET_SIZE];
+        loop {
+            let (length, from) = match self.socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(error) => return Err(error),
+            };
+            let Some(packet) = std::str::from_utf8(&buffer[..length])
+                .ok()
+                .and_then(|source| ron::from_str::<Packet>(source).ok())
+            else {
+                continue;
+            };
+            if from != self.peer
+                || packet.round != self.round
+                || packet.player_id != self.remote_player()
+            {
+                continue;
+            }
+            for (tick, input) in (packet.first_tick..).zip(packet.inputs) {
+                if tick >= self.delay {
+                    self.remote.entry(tick).or_insert(input);
+                }
+            }
+            for (tick, checksum) in packet.checksums {
+                self.remote_checksums.insert(tick, checksum);
+                self.compare_checksum(tick);
+            }
+        }
+    }
+}
+
+fn empty_ticks(delay: u64) -> usize {
+    usize::try_from(delay).unwrap_or(usize::MAX)
+}
+
+/// Steers a snake in a network game with the inputs both peers agreed on for each tick.
+///
+/// The local player's controller wraps the one actually playing: whenever the tick timer fires
+/// and the lockstep wants the next local input, it asks that controller and schedules the answer.
+pub struct NetworkController {
+    local: Option<Box<dyn SnakeController>>,
+    next: Option<Direction>,
+}
+
+impl NetworkController {
+    #[must_use]
+    pub fn local(controller: impl SnakeController + 'static) -> Self {
+        Self {
+            local: Some(Box::new(controller)),
+            next: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn remote() -> Self {
+        Self {
+            local: None,
+            next: None,
+        }
+    }
+}
+
+impl SnakeController for NetworkController {
+    fn next_direction(&mut self, _view: BoardView) -> Option<Direction> {
+        self.next.take()
+    }
+
+    fn read_input(&mut self, view: BoardView, input: &mut ControlInput) {
+        if let Some(local) = &mut self.local {
+            local.read_input(view, input);
+        }
+        let tick_due = input.tick_due();
+        let Some(lockstep) = input.lockstep.as_deref_mut() else {
+            return;
+        };
+        if let Some(local) = &mut self.local {
+            if tick_due && lockstep.needs_input(view.tick()) {
+                lockstep.schedule(local.next_direction(view));
+            }
+        }
+        self.next = lockstep.input(view.player_id(), view.tick());
+    }
+
+    fn restart(&mut self) {
+        self.next = None;
+        if let Some(local) = &mut self.local {
+            local.restart();
+        }
+    }
+}
+
+/// Takes in the peer's packets every frame, before controllers look for its inputs.
+pub fn receive_system(mut lockstep: ResMut<Lockstep>) {
+    if let Err(error) = lockstep.receive() {
+        eprintln!("network: {error}");
+    }
+}
+
+/// Sends the latest local inputs every frame, after controllers had their say, and reports
+/// the first desync.
+#[allow(clippy::needless_pass_by_value)]
+pub fn send_system(lockstep: Res<Lockstep>, mut reported: Local<Option<u64>>) {
+    if let Err(error) = lockstep.send() {
+        eprintln!("network: {error}");
+    }
+    if lockstep.desync() != *reported {
+        if let Some(tick) = lockstep.desync() {
+            eprintln!("Desync with the other player at tick {tick}");
+        }
+        *reported = lockstep.desync();
+    }
+}
+
+/// Run condition for `GameTick` in network games, in place of the tick timer.
+#[allow(clippy::needless_pass_by_value)]
+pub fn tick_ready(state: Res<GameState>, lockstep: Res<Lockstep>) -> bool {
+    lockstep.ready(state.tick)
+}
+
+/// Checks the state at the start of every tick against the peer's.
+#[allow(clippy::needless_pass_by_value)]
+pub fn checksum_system(state: Res<GameState>, mut lockstep: ResMut<Lockstep>) {
+    lockstep.record_checksum(state.tick, state.checksum());
+}
+
+pub fn reset_system(mut lockstep: ResMut<Lockstep>) {
+    lockstep.reset();
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        components::GameEndEvent,
+        controller::{self, Controllers, ScriptedController},
+        food,
+        rng::GameRng,
+        snake::{self, GrowthEvent, PlayerConfigs, Segments},
+        tick::{GameTick, TickTimer},
+    };
+
+    fn pair(delay: u64) -> (Lockstep, Lockstep) {
+        let first = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let second = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let (first_addr, second_addr) = (first.local_addr().unwrap(), second.local_addr().unwrap());
+        (
+            Lockstep::new(first, second_addr, 0, delay).unwrap(),
+            Lockstep::new(second, first_addr, 1, delay).unwrap(),
+        )
+    }
+
+    /// Loopback packets arrive quickly but not necessarily before the next call.
+    fn receive_until(lockstep: &mut Lockstep, done: impl Fn(&Lockstep) -> bool) {
+        for _ in 0..200 {
+            lockstep.receive().unwrap();
+            if done(lockstep) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        panic!("nothing arrived from the peer");
+    }
+
+    /// A peer whose local player makes `first_turn` and then goes straight.
+    fn peer_app(lockstep: Lockstep, first_turn: Direction) -> App {
+        let local = NetworkController::local(ScriptedController::new([Some(first_turn)]));
+        let controllers = if lockstep.local_player() == 0 {
+            Controllers::new(2)
+                .with(0, local)
+                .with(1, NetworkController::remote())
+        } else {
+            Controllers::new(2)
+                .with(0, NetworkController::remote())
+                .with(1, local)
+        };
+        let mut app = App::new();
+        app.insert_resource(Segments::default())
+            .insert_resource(GameState::default())
+            .insert_resource(GameRng::new(8))
+            .init_resource::<PlayerConfigs>()
+            .init_resource::<TickTimer>()
+            .insert_resource(controllers)
+            .insert_resource(lockstep)
+            .add_event::<GrowthEvent>()
+            .add_event::<GameEndEvent>()
+            .add_startup_system(snake::spawn_system)
+            .configure_set(GameTick.run_if(tick_ready))
+            .add_systems(
+                (
+                    receive_system,
+                    controller::controller_input_system,
+                    send_system,
+                )
+                    .chain()
+                    .before(GameTick),
+            )
+            .add_systems(
+                (
+                    checksum_system,
+                    controller::controller_system,
+                    snake::movement_system,
+                    snake::eating_system,
+                    snake::growth_system,
+                    food::spawn_system,
+                )
+                    .chain()
+                    .in_set(GameTick),
+            );
+        app
+    }
+
+    #[test]
+    fn only_players_0_and_1_can_play() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let peer = socket.local_addr().unwrap();
+
+        let error = Lockstep::new(socket, peer, 2, 0).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn inputs_apply_after_the_delay_once_both_are_known() {
+        let (mut first, mut second) = pair(2);
+        assert!(first.needs_input(0));
+        assert!(!first.ready(0));
+
+        first.schedule(Some(Direction::Left));
+        second.schedule(None);
+        assert_eq!(first.scheduled(), 3);
+        assert!(!first.needs_input(0));
+        assert_eq!(first.inputs(0), Some(vec![None, None]));
+
+        first.send().unwrap();
+        second.send().unwrap();
+        receive_until(&mut first, |lockstep| lockstep.remote.contains_key(&2));
+        receive_until(&mut second, |lockstep| lockstep.remote.contains_key(&2));
+        for lockstep in [&mut first, &mut second] {
+            assert!(!lockstep.ready(2));
+            lockstep.schedule(None);
+            lockstep.schedule(None);
+        }
+
+        assert_eq!(first.inputs(2), Some(vec![Some(Direction::Left), None]));
+        assert_eq!(second.inputs(2), Some(vec![Some(Direction::Left), None]));
+    }
+
+    #[test]
+    fn differing_checksums_are_reported_as_a_desync() {
+        let (mut first, mut second) = pair(1);
+        first.record_checksum(0, 7);
+        first.record_checksum(1, 8);
+        second.record_checksum(0, 7);
+        second.record_checksum(1, 9);
+
+        second.send().unwrap();
+        receive_until(&mut first, |lockstep| lockstep.desync().is_some());
+
+        assert_eq!(first.desync(), Some(1));
+    }
+
+    #[test]
+    fn packets_from_a_previous_round_are_ignored() {
+        let (mut first, mut second) = pair(0);
+        second.schedule(Some(Direction::Up));
+        second.send().unwrap();
+        first.reset();
+        second.reset();
+        second.schedule(Some(Direction::Down));
+        second.send().unwrap();
+
+        receive_until(&mut first, |lockstep| lockstep.remote.contains_key(&0));
+        first.schedule(None);
+
+        assert_eq!(first.inputs(0), Some(vec![None, Some(Direction::Down)]));
+    }
+
+    #[test]
+    fn two_peers_play_the_same_game() {
+        // The snakes meet head on a few ticks later.
+        const TICKS: u64 = 8;
+        let (first, second) = pair(3);
+        // Both snakes start heading up.
+        let mut peers = [
+            peer_app(first, Direction::Right),
+            peer_app(second, Direction::Left),
+        ];
+
+        for _ in 0..2000 {
+            for app in &mut peers {
+                if app.world.resource::<GameState>().tick < TICKS {
+                    let mut timer = app.world.resource_mut::<TickTimer>();
+                    let interval = timer.duration();
+                    timer.tick(interval);
+                    app.update();
+                }
+            }
+            if peers
+                .iter()
+                .all(|app| app.world.resource::<GameState>().tick >= TICKS)
+            {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let [first, second] = &peers;
+        let state = first.world.resource::<GameState>();
+        assert_eq!(state.tick, TICKS);
+        assert_eq!(state, second.world.resource::<GameState>());
+        assert_eq!(state.snakes[0].direction, Direction::Right);
+        assert_eq!(state.snakes[1].direction, Direction::Left);
+        assert_eq!(first.world.resource::<Lockstep>().desync(), None);
+        assert_eq!(second.world.resource::<Lockstep>().desync(), None);
+    }
+}
@@ -0,0 +1,309 @@
+use std::{fmt, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::Direction,
+    controller::{Controllers, ScriptedController},
+    food::FoodConfig,
+    grid::BoardConfig,
+    rng::GameRng,
+    simulation::{GameState, SpawnPoint},
+};
+
+/// Everything needed to play a game again: its starting setup and every tick's inputs.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replay {
+    /// Generator state when the game started, which is only the seed for the first game.
+    pub rng: GameRng,
+    pub board: BoardConfig,
+    pub spawn_points: Vec<SpawnPoint>,
+    pub food: FoodConfig,
+    /// Inputs for every tick, indexed by player id.
+    pub inputs: Vec<Vec<Option<Direction>>>,
+    /// Checksum of the final state, once the game is over.
+    pub checksum: Option<u64>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    Mismatch { expected: u64, actual: u64 },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "could not access replay: {error}"),
+            Self::Parse(error) => write!(f, "invalid replay: {error}"),
+            Self::Serialize(error) => write!(f, "could not write replay: {error}"),
+            Self::Mismatch { expected, actual } => write!(
+                f,
+                "replay diverged: expected state {expected:016x}, got {actual:016x}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl Replay {
+    /// Starts recording a game that is about to begin from `state`.
+    #[must_use]
+    pub fn start(state: &GameState, rng: &GameRng) -> Self {
+        Self {
+            rng: rng.clone(),
+            board: state.board,
+            spawn_points: state.spawn_points.clone(),
+            food: state.food_config,
+            inputs: Vec::new(),
+            checksum: None,
+        }
+    }
+
+    pub fn record(&mut self, inputs: &[Option<Direction>]) {
+        self.inputs.push(inputs.to_vec());
+    }
+
+    pub fn finish(&mut self, state: &GameState) {
+        self.checksum = Some(state.checksum());
+    }
+
+    #[must_use]
+    pub fn initial_state(&self) -> GameState {
+        GameState::with_spawn_points(self.board, self.spawn_points.iter().cloned())
+            .with_food(self.food)
+    }
+
+    /// One scripted controller per player, feeding the recorded inputs back tick by tick.
+    #[allow(clippy::cast_possible_truncation)]
+    #[must_use]
+    pub fn controllers(&self) -> Controllers {
+        (0..self.spawn_points.len() as u8).fold(Controllers::default(), |controllers, player_id| {
+            let turns = self
+                .inputs
+                .iter()
+                .map(|inputs| inputs.get(usize::from(player_id)).copied().flatten());
+            controllers.with(player_id, ScriptedController::new(turns))
+        })
+    }
+
+    /// Runs the recorded inputs through the core rules, without an `App`.
+    #[must_use]
+    pub fn play(&self) -> GameState {
+        let mut state = self.initial_state();
+        let mut rng = self.rng.clone();
+        for inputs in &self.inputs {
+            state.step(inputs);
+            state.spawn_due_food(&mut rng);
+        }
+        state
+    }
+
+    /// Checks `state` against the recorded final checksum. Unfinished replays always match.
+    pub fn verify(&self, state: &GameState) -> Result<(), ReplayError> {
+        let actual = state.checksum();
+        match self.checksum {
+            Some(expected) if expected != actual => Err(ReplayError::Mismatch { expected, actual }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn to_ron(&self) -> Result<String, ReplayError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(ReplayError::Serialize)
+    }
+
+    pub fn from_ron(source: &str) -> Result<Self, ReplayError> {
+        ron::from_str(source).map_err(ReplayError::Parse)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ReplayError> {
+        std::fs::write(path, self.to_ron()?).map_err(ReplayError::Io)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        Self::from_ron(&std::fs::read_to_string(path).map_err(ReplayError::Io)?)
+    }
+}
+
+/// The game being recorded, if any. `movement_system` adds each tick's inputs.
+#[derive(Resource, Debug, Default)]
+pub struct ReplayRecorder {
+    pub replay: Option<Replay>,
+    /// Where finished replays are written.
+    pub path: Option<String>,
+}
+
+/// The replay being played back, checked when the game ends.
+#[derive(Resource, Debug)]
+pub struct ReplayPlayback(pub Replay);
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn start_recording_system(
+    state: Res<GameState>,
+    rng: Res<GameRng>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    recorder.replay = Some(Replay::start(&state, &rng));
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn finish_recording_system(state: Res<GameState>, mut recorder: ResMut<ReplayRecorder>) {
+    let ReplayRecorder { replay, path } = &mut *recorder;
+    let Some(replay) = replay.as_mut() else {
+        return;
+    };
+    replay.finish(&state);
+    if let Some(path) = path {
+        match replay.save(&path) {
+            Ok(()) => println!("Replay saved to {path}"),
+            Err(error) => eprintln!("{error}"),
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn verify_playback_system(state: Res<GameState>, playback: Res<ReplayPlayback>) {
+    match playback.0.verify(&state) {
+        Ok(()) => println!("Replay verified"),
+        Err(error) => eprintln!("{error}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        bot::{BotController, BotLevel},
+        components::GameEndEvent,
+        controller,
+        food::{self, FoodSpawn},
+        snake::{self, PlayerConfigs, Segments},
+    };
+
+    fn bot_game() -> (GameState, Replay) {
+        let mut state = GameState::new(BoardConfig::new(12, 12), 2).with_food(FoodConfig {
+            max_food: 2,
+            spawn: FoodSpawn::OnEat,
+        });
+        let mut rng = GameRng::new(11);
+        let mut replay = Replay::start(&state, &rng);
+        let mut bots = Controllers::new(2)
+            .with(0, BotController(BotLevel::Safe))
+            .with(1, BotController(BotLevel::Greedy));
+
+        for _ in 0..60 {
+            let inputs = bots.decide(&state);
+            replay.record(&inputs);
+            state.step(&inputs);
+            state.spawn_due_food(&mut rng);
+        }
+        replay.finish(&state);
+        (state, replay)
+    }
+
+    fn tick_app(state: GameState, rng: GameRng, controllers: Controllers) -> App {
+        let mut app = App::new();
+        app.insert_resource(Segments::default())
+            .insert_resource(state)
+            .insert_resource(rng)
+            .insert_resource(controllers)
+            .init_resource::<PlayerConfigs>()
+            .add_event::<GameEndEvent>()
+            .add_event::<snake::GrowthEvent>()
+            .add_startup_system(snake::spawn_system)
+            .add_systems(
+                (
+                    controller::controller_system,
+                    snake::movement_system,
+                    snake::eating_system,
+                    snake::growth_system,
+                    food::spawn_system,
+                )
+                    .chain(),
+            );
+        app
+    }
+
+    #[test]
+    fn replay_reproduces_the_recorded_game() {
+        let (state, replay) = bot_game();
+
+        let replayed = replay.play();
+
+        assert_eq!(replayed, state);
+        assert!(replay.verify(&replayed).is_ok());
+    }
+
+    #[test]
+    fn changed_inputs_fail_verification() {
+        let (_, mut replay) = bot_game();
+        // Player 0 starts heading up, so either sideways turn is accepted.
+        replay.inputs[0][0] = Some(if replay.inputs[0][0] == Some(Direction::Left) {
+            Direction::Right
+        } else {
+            Direction::Left
+        });
+
+        assert!(matches!(
+            replay.verify(&replay.play()),
+            Err(ReplayError::Mismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn replay_survives_a_ron_round_trip() {
+        let (_, replay) = bot_game();
+
+        let loaded = Replay::from_ron(&replay.to_ron().unwrap()).unwrap();
+
+        assert_eq!(loaded, replay);
+    }
+
+    #[test]
+    fn recording_and_playback_go_through_the_game_systems() {
+        let state = GameState::default().with_food(FoodConfig {
+            max_food: 3,
+            spawn: FoodSpawn::OnEat,
+        });
+        let rng = GameRng::new(21);
+        let bots = Controllers::new(2)
+            .with(0, BotController(BotLevel::LookAhead))
+            .with(1, BotController(BotLevel::Safe));
+
+        let mut recording = tick_app(state.clone(), rng.clone(), bots);
+        recording.insert_resource(ReplayRecorder {
+            replay: Some(Replay::start(&state, &rng)),
+            path: None,
+        });
+        for _ in 0..40 {
+            recording.update();
+        }
+        let mut replay = recording
+            .world
+            .resource_mut::<ReplayRecorder>()
+            .replay
+            .take()
+            .unwrap();
+        replay.finish(recording.world.resource::<GameState>());
+        assert_eq!(replay.inputs.len(), 40);
+
+        let mut playback = tick_app(
+            replay.initial_state(),
+            replay.rng.clone(),
+            replay.controllers(),
+        );
+        for _ in 0..40 {
+            playback.update();
+        }
+
+        assert!(replay
+            .verify(playback.world.resource::<GameState>())
+            .is_ok());
+        assert!(replay.verify(&replay.play()).is_ok());
+    }
+}
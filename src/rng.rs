@@ -0,0 +1,101 @@
+use bevy::prelude::Resource;
+use rand::{Error, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// Seeded source for all game randomness.
+///
+/// SplitMix64 keeps the whole generator in one `u64`, so a game can be replayed
+/// from its seed regardless of which `rand` version is in use.
+#[derive(Resource, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRng {
+    seed: u64,
+    state: u64,
+}
+
+impl GameRng {
+    #[must_use]
+    pub const fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    #[must_use]
+    pub fn from_entropy() -> Self {
+        Self::new(rand::random())
+    }
+
+    /// Same seed for everyone on the same UTC day.
+    #[must_use]
+    pub fn daily() -> Self {
+        let days = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() / 86_400);
+        Self::new(days)
+    }
+
+    #[must_use]
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn same_seed_gives_same_sequence() {
+        let mut a = GameRng::new(42);
+        let mut b = GameRng::new(42);
+
+        let a_values: Vec<u16> = (0..16).map(|_| a.gen_range(0..20)).collect();
+        let b_values: Vec<u16> = (0..16).map(|_| b.gen_range(0..20)).collect();
+
+        assert_eq!(a_values, b_values);
+        assert_eq!(a.seed(), 42);
+    }
+
+    #[test]
+    fn different_seeds_diverge() {
+        let mut a = GameRng::new(1);
+        let mut b = GameRng::new(2);
+
+        assert_ne!(a.next_u64(), b.next_u64());
+    }
+
+    #[test]
+    fn fill_bytes_handles_partial_chunks() {
+        let mut rng = GameRng::new(7);
+        let mut bytes = [0u8; 11];
+
+        rng.fill_bytes(&mut bytes);
+
+        assert_eq!(bytes[..8], GameRng::new(7).next_u64().to_le_bytes()[..]);
+    }
+}
@@ -0,0 +1,173 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{simulation::GameState, snake::GrowthEvent};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerScore {
+    pub points: u32,
+    pub food: u32,
+    pub length: usize,
+    pub ticks_alive: u64,
+}
+
+/// Scores for the current game, indexed by `Player::id`.
+#[derive(
+    Resource, Clone, Debug, Default, PartialEq, Eq, Deref, DerefMut, Serialize, Deserialize,
+)]
+pub struct Scoreboard(pub Vec<PlayerScore>);
+
+impl Scoreboard {
+    /// Empty scores with each snake's starting length.
+    #[must_use]
+    pub fn from_state(state: &GameState) -> Self {
+        Self(
+            state
+                .snakes
+                .iter()
+                .map(|snake| PlayerScore {
+                    length: snake.body.len(),
+                    ..PlayerScore::default()
+                })
+                .collect(),
+        )
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScoreRules {
+    pub per_food: u32,
+    pub per_tick_alive: u32,
+}
+
+impl Default for ScoreRules {
+    fn default() -> Self {
+        Self {
+            per_food: 10,
+            per_tick_alive: 1,
+        }
+    }
+}
+
+/// Runs once per game tick, after growth.
+#[allow(clippy::needless_pass_by_value)]
+pub fn scoreboard_system(
+    state: Res<GameState>,
+    rules: Res<ScoreRules>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut growth_reader: EventReader<GrowthEvent>,
+) {
+    if scoreboard.len() < state.snakes.len() {
+        scoreboard.resize(state.snakes.len(), PlayerScore::default());
+    }
+    for event in growth_reader.iter() {
+        if let Some(score) = scoreboard.get_mut(usize::from(event.player_id)) {
+            score.food += 1;
+            score.points += rules.per_food;
+        }
+    }
+    for (score, snake) in scoreboard.iter_mut().zip(&state.snakes) {
+        score.length = snake.body.len();
+        if snake.is_alive() {
+            score.ticks_alive += 1;
+            score.points += rules.per_tick_alive;
+        }
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn reset_system(state: Res<GameState>, mut scoreboard: ResMut<Scoreboard>) {
+    *scoreboard = Scoreboard::from_state(&state);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        components::Position,
+        snake::{self, PlayerConfigs, Segments},
+    };
+
+    #[test]
+    fn eating_and_surviving_score_points() {
+        // Setup
+        let mut app = App::new();
+        let state = GameState {
+            food: vec![Position { x: 3, y: 4 }],
+            ..GameState::default()
+        };
+
+        // Add systems
+        app.insert_resource(Segments::default())
+            .insert_resource(Scoreboard::from_state(&state))
+            .insert_resource(state)
+            .init_resource::<PlayerConfigs>()
+            .init_resource::<ScoreRules>()
+            .add_event::<GrowthEvent>()
+            .add_event::<crate::components::GameEndEvent>()
+            .add_startup_system(snake::spawn_system)
+            .add_systems(
+                (
+                    snake::movement_system,
+                    snake::eating_system,
+                    snake::growth_system,
+                    scoreboard_system,
+                )
+                    .chain(),
+            );
+
+        // Run systems
+        app.update();
+        app.update();
+
+        let scoreboard = app.world.resource::<Scoreboard>();
+        assert_eq!(
+            scoreboard[0],
+            PlayerScore {
+                points: 12,
+                food: 1,
+                length: 3,
+                ticks_alive: 2,
+            }
+        );
+        assert_eq!(
+            scoreboard[1],
+            PlayerScore {
+                points: 2,
+                food: 0,
+                length: 2,
+                ticks_alive: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn reset_restores_starting_lengths() {
+        let mut app = App::new();
+        let state = GameState::default();
+        app.insert_resource(Scoreboard(vec![
+            PlayerScore {
+                points: 40,
+                food: 3,
+                length: 5,
+                ticks_alive: 10,
+            };
+            2
+        ]))
+        .insert_resource(state)
+        .add_system(reset_system);
+
+        app.update();
+
+        assert_eq!(
+            app.world.resource::<Scoreboard>().0,
+            vec![
+                PlayerScore {
+                    length: 2,
+                    ..PlayerScore::default()
+                };
+                2
+            ]
+        );
+    }
+}
@@ -0,0 +1,842 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use bevy::prelude::Resource;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{Collision, Direction, GameEndEvent, Player, Position},
+    food::{FoodConfig, FoodSpawn},
+    grid::BoardConfig,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GameEvent {
+    Ate { player_id: u8, position: Position },
+    Grew { player_id: u8, position: Position },
+    Died { player_id: u8, collision: Collision },
+    GameEnd(GameEndEvent),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Snake {
+    pub player: Player,
+    pub direction: Direction,
+    /// Ordered from head to tail.
+    pub body: VecDeque<Position>,
+    /// Where the tail was before the last move, so growth attaches to this snake.
+    pub last_tail_position: Option<Position>,
+    /// Set once the snake has died.
+    pub collision: Option<Collision>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SpawnPoint {
+    pub head: Position,
+    pub direction: Direction,
+}
+
+impl SpawnPoint {
+    /// Default layout: one snake per corner, moving away from its edge,
+    /// with every further group of four placed two cells further in.
+    #[allow(clippy::cast_possible_wrap)]
+    #[must_use]
+    pub fn for_player(player_id: u8, board: &BoardConfig) -> Self {
+        let (width, height) = (board.width as i16, board.height as i16);
+        let inset = i16::from(player_id / 4) * 2;
+        let (x, y, direction) = match player_id % 4 {
+            0 => (3 + inset, 3, Direction::Up),
+            1 => (width - 3 - inset, 3, Direction::Up),
+            2 => (3 + inset, height - 4, Direction::Down),
+            _ => (width - 3 - inset, height - 4, Direction::Down),
+        };
+        Self {
+            head: Position { x, y },
+            direction,
+        }
+    }
+}
+
+/// Why a game's snakes cannot all start where they are placed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpawnError {
+    NoPlayers,
+    OffBoard { player_id: u8 },
+    Overlap { player_id: u8, other: u8 },
+}
+
+impl fmt::Display for SpawnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoPlayers => write!(f, "a game needs at least one player"),
+            Self::OffBoard { player_id } => {
+                write!(f, "player {} does not fit on the board", player_id + 1)
+            }
+            Self::Overlap { player_id, other } => write!(
+                f,
+                "players {} and {} would start on top of each other",
+                other + 1,
+                player_id + 1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SpawnError {}
+
+impl Snake {
+    #[must_use]
+    pub fn new(player_id: u8, board: &BoardConfig) -> Self {
+        Self::spawn(player_id, SpawnPoint::for_player(player_id, board))
+    }
+
+    #[must_use]
+    pub fn spawn(player_id: u8, spawn_point: SpawnPoint) -> Self {
+        let tail = next_position(&spawn_point.head, spawn_point.direction.opposite());
+        Self {
+            player: Player { id: player_id },
+            direction: spawn_point.direction,
+            body: VecDeque::from([spawn_point.head, tail]),
+            last_tail_position: None,
+            collision: None,
+        }
+    }
+
+    #[must_use]
+    pub fn head(&self) -> &Position {
+        &self.body[0]
+    }
+
+    #[must_use]
+    pub const fn is_alive(&self) -> bool {
+        self.collision.is_none()
+    }
+
+    pub fn turn(&mut self, direction: Direction) {
+        if direction != self.direction.opposite() {
+            self.direction = direction;
+        }
+    }
+
+    /// The cell the tail just left, if the head moved onto food: growing puts the tail straight
+    /// back there, so the cell never frees up.
+    fn kept_tail(&self, food: &[Position]) -> Option<&Position> {
+        self.last_tail_position
+            .as_ref()
+            .filter(|_| self.is_alive() && food.contains(self.head()))
+    }
+}
+
+/// Deterministic game rules, stepped without an `App`.
+#[derive(Resource, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GameState {
+    pub board: BoardConfig,
+    pub spawn_points: Vec<SpawnPoint>,
+    pub snakes: Vec<Snake>,
+    pub food: Vec<Position>,
+    pub food_config: FoodConfig,
+    pub outcome: Option<GameEndEvent>,
+    pub tick: u64,
+    /// Food eaten by all snakes since the last restart.
+    pub food_eaten: u32,
+}
+
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new(BoardConfig::default(), 2)
+    }
+}
+
+impl GameState {
+    #[must_use]
+    pub fn new(board: BoardConfig, players: u8) -> Self {
+        Self::with_spawn_points(
+            board,
+            (0..players).map(|player_id| SpawnPoint::for_player(player_id, &board)),
+        )
+    }
+
+    /// One snake per spawn point, with player ids in iteration order.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn with_spawn_points(
+        board: BoardConfig,
+        spawn_points: impl IntoIterator<Item = SpawnPoint>,
+    ) -> Self {
+        let spawn_points: Vec<SpawnPoint> = spawn_points.into_iter().collect();
+        Self {
+            board,
+            snakes: spawn_points
+                .iter()
+                .enumerate()
+                .map(|(player_id, spawn_point)| Snake::spawn(player_id as u8, spawn_point.clone()))
+                .collect(),
+            spawn_points,
+            food: Vec::new(),
+            food_config: FoodConfig::default(),
+            outcome: None,
+            tick: 0,
+            food_eaten: 0,
+        }
+    }
+
+    /// Fails unless there is at least one snake and every snake starts on the board, clear of
+    /// the others.
+    pub fn check_spawns(&self) -> Result<(), SpawnError> {
+        if self.snakes.is_empty() {
+            return Err(SpawnError::NoPlayers);
+        }
+        let mut occupied = HashMap::new();
+        for snake in &self.snakes {
+            let player_id = snake.player.id;
+            for position in &snake.body {
+                if !self.board.contains(position) {
+                    return Err(SpawnError::OffBoard { player_id });
+                }
+                if let Some(other) = occupied.insert(position, player_id) {
+                    return Err(SpawnError::Overlap { player_id, other });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[must_use]
+    pub const fn with_food(mut self, food_config: FoodConfig) -> Self {
+        self.food_config = food_config;
+        self
+    }
+
+    /// Puts every snake back on its spawn point and clears food, outcome and tick count.
+    pub fn restart(&mut self) {
+        *self = Self::with_spawn_points(self.board, std::mem::take(&mut self.spawn_points))
+            .with_food(self.food_config);
+    }
+
+    /// Runs a full tick: movement, eating and growth. `inputs` is indexed by player id.
+    pub fn step(&mut self, inputs: &[Option<Direction>]) -> Vec<GameEvent> {
+        let mut events = self.move_snakes(inputs);
+        let eaten = self.eat();
+        for event in eaten {
+            if let GameEvent::Ate { player_id, .. } = event {
+                events.push(event);
+                if let Some(position) = self.grow(player_id) {
+                    events.push(GameEvent::Grew {
+                        player_id,
+                        position,
+                    });
+                }
+            }
+        }
+        events
+    }
+
+    pub fn move_snakes(&mut self, inputs: &[Option<Direction>]) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        if self.outcome.is_some() {
+            return events;
+        }
+
+        let board = self.board;
+        for (player_id, snake) in self.snakes.iter_mut().enumerate() {
+            if !snake.is_alive() {
+                continue;
+            }
+            if let Some(Some(direction)) = inputs.get(player_id) {
+                snake.turn(*direction);
+            }
+            snake.last_tail_position = snake.body.back().cloned();
+
+            let head = board.wrap(next_position(snake.head(), snake.direction));
+            snake.body.pop_back();
+            snake.body.push_front(head);
+        }
+
+        let collisions: Vec<(usize, Collision)> = (0..self.snakes.len())
+            .filter(|index| self.snakes[*index].is_alive())
+            .filter_map(|index| self.collision(index).map(|collision| (index, collision)))
+            .collect();
+        for (index, collision) in &collisions {
+            let snake = &mut self.snakes[*index];
+            snake.collision = Some(*collision);
+            events.push(GameEvent::Died {
+                player_id: snake.player.id,
+                collision: *collision,
+            });
+        }
+
+        if !collisions.is_empty() {
+            self.outcome = self.resolve_outcome(collisions.len());
+            if let Some(outcome) = &self.outcome {
+                events.push(GameEvent::GameEnd(outcome.clone()));
+            }
+        }
+        self.tick += 1;
+        events
+    }
+
+    /// Checks the head of `self.snakes[index]` against the board after every snake has moved.
+    fn collision(&self, index: usize) -> Option<Collision> {
+        let snake = &self.snakes[index];
+        let head = snake.head();
+        if !self.board.contains(head) {
+            return Some(Collision::Wall);
+        }
+        if snake.body.iter().skip(1).any(|position| position == head) {
+            return Some(Collision::Itself);
+        }
+        self.snakes
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != index)
+            .find(|(_, other)| {
+                other.body.contains(head) || other.kept_tail(&self.food) == Some(head)
+            })
+            .map(|(_, other)| Collision::Opponent(other.player.id))
+    }
+
+    fn resolve_outcome(&self, deaths_this_tick: usize) -> Option<GameEndEvent> {
+        let mut alive = self.snakes.iter().filter(|snake| snake.is_alive());
+        match (alive.next(), alive.next()) {
+            (Some(winner), None) if self.snakes.len() > 1 => {
+                Some(GameEndEvent::Winner(winner.player.clone()))
+            }
+            (None, _) if deaths_this_tick > 1 => Some(GameEndEvent::Draw),
+            (None, _) => Some(GameEndEvent::AllDead),
+            _ => None,
+        }
+    }
+
+    pub fn eat(&mut self) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        for snake in self.snakes.iter().filter(|snake| snake.is_alive()) {
+            if let Some(index) = self.food.iter().position(|food| food == snake.head()) {
+                events.push(GameEvent::Ate {
+                    player_id: snake.player.id,
+                    position: self.food.swap_remove(index),
+                });
+                self.food_eaten += 1;
+            }
+        }
+        events
+    }
+
+    pub fn grow(&mut self, player_id: u8) -> Option<Position> {
+        let snake = self.snakes.get_mut(usize::from(player_id))?;
+        let position = snake.last_tail_position.clone()?;
+        snake.body.push_back(position.clone());
+        Some(position)
+    }
+
+    /// Places one food on a random free cell, unless the food cap is reached or the board is full.
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn spawn_food(&mut self, rng: &mut impl Rng) -> Option<Position> {
+        if self.food.len() >= self.food_config.max_food {
+            return None;
+        }
+        let occupied: HashSet<&Position> = self
+            .snakes
+            .iter()
+            .flat_map(|snake| snake.body.iter())
+            .chain(self.food.iter())
+            .collect();
+
+        let board = self.board;
+        let free_cells: Vec<Position> = (0..board.height as i16)
+            .flat_map(|y| (0..board.width as i16).map(move |x| Position { x, y }))
+            .filter(|position| !occupied.contains(position))
+            .collect();
+        if free_cells.is_empty() {
+            return None;
+        }
+        let position = free_cells[rng.gen_range(0..free_cells.len())].clone();
+        self.food.push(position.clone());
+        Some(position)
+    }
+
+    /// Spawns food until the cap is reached or no free cell is left.
+    pub fn refill_food(&mut self, rng: &mut impl Rng) -> Vec<Position> {
+        std::iter::from_fn(|| self.spawn_food(rng)).collect()
+    }
+
+    /// FNV-1a hash of the whole state, so two runs can be compared without shipping the state.
+    #[must_use]
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = Fnv1a::default();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Spawns whatever food `food_config.spawn` asks for at the current tick.
+    pub fn spawn_due_food(&mut self, rng: &mut impl Rng) -> Vec<Position> {
+        match self.food_config.spawn {
+            FoodSpawn::Interval(ticks) if self.tick.is_multiple_of(ticks.max(1)) => {
+                self.spawn_food(rng).into_iter().collect()
+            }
+            FoodSpawn::Interval(_) => Vec::new(),
+            FoodSpawn::OnEat => self.refill_food(rng),
+        }
+    }
+}
+
+/// Unlike `DefaultHasher`, FNV-1a is guaranteed not to change between Rust releases.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+#[must_use]
+pub const fn next_position(position: &Position, direction: Direction) -> Position {
+    let Position { x, y } = *position;
+    match direction {
+        Direction::Left => Position { x: x - 1, y },
+        Direction::Right => Position { x: x + 1, y },
+        Direction::Up => Position { x, y: y + 1 },
+        Direction::Down => Position { x, y: y - 1 },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{grid::EdgePolicy, rng::GameRng};
+
+    #[test]
+    fn step_moves_every_snake_in_its_direction() {
+        let mut state = GameState::default();
+
+        let events = state.step(&[Some(Direction::Right), None]);
+
+        assert!(events.is_empty());
+        assert_eq!(
+            state.snakes[0].body,
+            VecDeque::from([Position { x: 4, y: 3 }, Position { x: 3, y: 3 }])
+        );
+        assert_eq!(state.snakes[1].head(), &Position { x: 17, y: 4 });
+        assert_eq!(state.tick, 1);
+    }
+
+    #[test]
+    fn step_ignores_reversing_input() {
+        let mut state = GameState::default();
+
+        state.step(&[Some(Direction::Down), None]);
+
+        assert_eq!(state.snakes[0].direction, Direction::Up);
+        assert_eq!(state.snakes[0].head(), &Position { x: 3, y: 4 });
+    }
+
+    #[test]
+    fn snake_eats_and_grows_in_one_step() {
+        let mut state = GameState::default();
+        state.food.push(Position { x: 3, y: 4 });
+
+        let events = state.step(&[]);
+
+        assert_eq!(
+            events,
+            vec![
+                GameEvent::Ate {
+                    player_id: 0,
+                    position: Position { x: 3, y: 4 }
+                },
+                GameEvent::Grew {
+                    player_id: 0,
+                    position: Position { x: 3, y: 2 }
+                },
+            ]
+        );
+        assert!(state.food.is_empty());
+        assert_eq!(state.snakes[0].body.len(), 3);
+    }
+
+    #[test]
+    fn both_snakes_grow_at_their_own_tail_when_eating_in_the_same_step() {
+        let mut state = GameState::default();
+        let p2_x = 17;
+        state.food = vec![Position { x: 3, y: 4 }, Position { x: p2_x, y: 4 }];
+
+        let events = state.step(&[]);
+
+        assert!(events.contains(&GameEvent::Grew {
+            player_id: 0,
+            position: Position { x: 3, y: 2 }
+        }));
+        assert!(events.contains(&GameEvent::Grew {
+            player_id: 1,
+            position: Position { x: p2_x, y: 2 }
+        }));
+        assert_eq!(state.snakes[0].body.back(), Some(&Position { x: 3, y: 2 }));
+        assert_eq!(
+            state.snakes[1].body.back(),
+            Some(&Position { x: p2_x, y: 2 })
+        );
+    }
+
+    #[test]
+    fn leaving_the_grid_ends_the_game_and_freezes_it() {
+        let mut state = GameState::default();
+
+        for _ in 0..3 {
+            assert!(state.step(&[Some(Direction::Left), None]).is_empty());
+        }
+        let events = state.step(&[Some(Direction::Left), None]);
+
+        let winner = GameEndEvent::Winner(Player { id: 1 });
+        assert_eq!(
+            events,
+            vec![
+                GameEvent::Died {
+                    player_id: 0,
+                    collision: Collision::Wall
+                },
+                GameEvent::GameEnd(winner.clone()),
+            ]
+        );
+        assert_eq!(state.outcome, Some(winner));
+
+        let frozen = state.clone();
+        assert!(state.step(&[]).is_empty());
+        assert_eq!(state, frozen);
+    }
+
+    fn snake(id: u8, direction: Direction, body: &[(i16, i16)]) -> Snake {
+        Snake {
+            direction,
+            body: body.iter().map(|&(x, y)| Position { x, y }).collect(),
+            ..Snake::new(id, &BoardConfig::default())
+        }
+    }
+
+    #[test]
+    fn running_into_an_opponent_body_is_attributed_to_the_moving_head() {
+        let mut state = GameState {
+            snakes: vec![
+                snake(0, Direction::Right, &[(3, 3), (2, 3)]),
+                snake(1, Direction::Up, &[(4, 4), (4, 3), (4, 2)]),
+            ],
+            ..GameState::default()
+        };
+
+        let events = state.step(&[]);
+
+        assert_eq!(state.snakes[0].collision, Some(Collision::Opponent(1)));
+        assert!(state.snakes[1].is_alive());
+        assert_eq!(
+            events.last(),
+            Some(&GameEvent::GameEnd(GameEndEvent::Winner(Player { id: 1 })))
+        );
+    }
+
+    #[test]
+    fn tails_of_snakes_that_eat_stay_put() {
+        let mut state = GameState {
+            snakes: vec![
+                snake(0, Direction::Right, &[(3, 3), (2, 3)]),
+                snake(1, Direction::Up, &[(4, 5), (4, 4), (4, 3)]),
+            ],
+            food: vec![Position { x: 4, y: 6 }],
+            ..GameState::default()
+        };
+
+        state.step(&[]);
+
+        assert_eq!(state.snakes[0].collision, Some(Collision::Opponent(1)));
+        assert!(state.snakes[1].is_alive());
+        assert_eq!(state.snakes[1].body.back(), Some(&Position { x: 4, y: 3 }));
+    }
+
+    #[test]
+    fn running_into_itself_is_a_self_collision() {
+        let mut state = GameState {
+            snakes: vec![
+                snake(0, Direction::Up, &[(3, 3), (3, 2), (4, 2), (4, 3), (4, 4)]),
+                Snake::new(1, &BoardConfig::default()),
+            ],
+            ..GameState::default()
+        };
+
+        state.step(&[Some(Direction::Right), None]);
+
+        assert_eq!(state.snakes[0].collision, Some(Collision::Itself));
+        assert_eq!(state.outcome, Some(GameEndEvent::Winner(Player { id: 1 })));
+    }
+
+    #[test]
+    fn head_on_collision_is_a_draw() {
+        let mut state = GameState {
+            snakes: vec![
+                snake(0, Direction::Right, &[(3, 3), (2, 3)]),
+                snake(1, Direction::Left, &[(5, 3), (6, 3)]),
+            ],
+            ..GameState::default()
+        };
+
+        let events = state.step(&[]);
+
+        assert_eq!(state.snakes[0].collision, Some(Collision::Opponent(1)));
+        assert_eq!(state.snakes[1].collision, Some(Collision::Opponent(0)));
+        assert_eq!(events.last(), Some(&GameEvent::GameEnd(GameEndEvent::Draw)));
+    }
+
+    #[test]
+    fn solo_snake_dying_ends_with_all_dead() {
+        let mut state = GameState::new(BoardConfig::default(), 1);
+
+        for _ in 0..4 {
+            state.step(&[Some(Direction::Left)]);
+        }
+
+        assert_eq!(state.outcome, Some(GameEndEvent::AllDead));
+    }
+
+    #[test]
+    fn bounds_come_from_the_board_config() {
+        let mut state = GameState::new(BoardConfig::new(30, 5), 2);
+        assert_eq!(state.snakes[1].head(), &Position { x: 27, y: 3 });
+
+        state.step(&[]);
+        assert!(state.outcome.is_none());
+        state.step(&[]);
+
+        assert_eq!(state.outcome, Some(GameEndEvent::Draw));
+    }
+
+    #[test]
+    fn wrapping_board_sends_heads_to_the_opposite_edge() {
+        let mut state = GameState::new(BoardConfig::new(10, 10).with_edges(EdgePolicy::Wrap), 2);
+
+        for _ in 0..4 {
+            state.step(&[Some(Direction::Left), None]);
+        }
+
+        assert!(state.outcome.is_none());
+        assert_eq!(
+            state.snakes[0].body,
+            VecDeque::from([Position { x: 9, y: 3 }, Position { x: 0, y: 3 }])
+        );
+    }
+
+    #[test]
+    fn collisions_are_detected_across_the_seam() {
+        let mut state = GameState {
+            snakes: vec![
+                snake(0, Direction::Left, &[(0, 3), (1, 3)]),
+                snake(1, Direction::Up, &[(9, 2), (9, 1), (9, 0)]),
+            ],
+            ..GameState::new(BoardConfig::new(10, 10).with_edges(EdgePolicy::Wrap), 2)
+        };
+
+        state.step(&[Some(Direction::Left), None]);
+
+        assert_eq!(state.snakes[0].head(), &Position { x: 9, y: 3 });
+        assert_eq!(state.snakes[0].collision, Some(Collision::Opponent(1)));
+    }
+
+    #[test]
+    fn four_players_spawn_in_the_corners() {
+        let state = GameState::new(BoardConfig::default(), 4);
+
+        let heads: Vec<&Position> = state.snakes.iter().map(Snake::head).collect();
+        assert_eq!(
+            heads,
+            vec![
+                &Position { x: 3, y: 3 },
+                &Position { x: 17, y: 3 },
+                &Position { x: 3, y: 16 },
+                &Position { x: 17, y: 16 },
+            ]
+        );
+        assert_eq!(state.snakes[3].body[1], Position { x: 17, y: 17 });
+    }
+
+    #[test]
+    fn game_goes_on_until_one_of_three_snakes_is_left() {
+        let mut state = GameState::new(BoardConfig::default(), 3);
+
+        for _ in 0..3 {
+            state.step(&[Some(Direction::Left), None, None]);
+        }
+        let events = state.step(&[Some(Direction::Left), None, None]);
+
+        assert_eq!(state.snakes[0].collision, Some(Collision::Wall));
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, GameEvent::GameEnd(_))));
+
+        for _ in 0..3 {
+            state.step(&[None, None, Some(Direction::Left)]);
+        }
+        state.step(&[None, None, Some(Direction::Left)]);
+
+        assert_eq!(state.outcome, Some(GameEndEvent::Winner(Player { id: 1 })));
+    }
+
+    #[test]
+    fn spawns_are_checked_for_room() {
+        let board = BoardConfig::default();
+
+        assert_eq!(
+            GameState::new(board, 0).check_spawns(),
+            Err(SpawnError::NoPlayers)
+        );
+        assert_eq!(GameState::new(board, 16).check_spawns(), Ok(()));
+        assert_eq!(
+            GameState::new(board, 17).check_spawns(),
+            Err(SpawnError::Overlap {
+                player_id: 16,
+                other: 13
+            })
+        );
+        assert_eq!(
+            GameState::new(BoardConfig::new(3, 3), 1).check_spawns(),
+            Err(SpawnError::OffBoard { player_id: 0 })
+        );
+    }
+
+    #[test]
+    fn custom_spawn_points_are_used_in_order() {
+        let state = GameState::with_spawn_points(
+            BoardConfig::default(),
+            [SpawnPoint {
+                head: Position { x: 5, y: 5 },
+                direction: Direction::Right,
+            }],
+        );
+
+        assert_eq!(state.snakes.len(), 1);
+        assert_eq!(
+            state.snakes[0].body,
+            VecDeque::from([Position { x: 5, y: 5 }, Position { x: 4, y: 5 }])
+        );
+    }
+
+    #[test]
+    fn restart_puts_snakes_back_on_their_spawn_points() {
+        let mut state = GameState::new(BoardConfig::default(), 3);
+        let fresh = state.clone();
+        state.food.push(Position { x: 3, y: 4 });
+        for _ in 0..5 {
+            state.step(&[Some(Direction::Left), None, None]);
+        }
+        assert!(state.outcome.is_none() && state.snakes[0].collision.is_some());
+
+        state.restart();
+
+        assert_eq!(state, fresh);
+    }
+
+    #[test]
+    fn food_never_spawns_on_a_snake() {
+        let state = GameState::with_spawn_points(
+            BoardConfig::new(3, 1),
+            [SpawnPoint {
+                head: Position { x: 1, y: 0 },
+                direction: Direction::Right,
+            }],
+        );
+
+        for seed in 0..50 {
+            let spawned = state.clone().spawn_food(&mut GameRng::new(seed));
+            assert_eq!(spawned, Some(Position { x: 2, y: 0 }));
+        }
+
+        let mut full = GameState {
+            food: vec![Position { x: 2, y: 0 }],
+            ..state
+        };
+        assert_eq!(full.spawn_food(&mut GameRng::new(0)), None);
+    }
+
+    #[test]
+    fn food_fills_a_nearly_full_board() {
+        let mut state = GameState::new(BoardConfig::new(4, 4), 1).with_food(FoodConfig {
+            max_food: usize::MAX,
+            ..FoodConfig::default()
+        });
+        let mut rng = GameRng::new(11);
+
+        let spawned = state.refill_food(&mut rng);
+
+        assert_eq!(spawned.len(), 14);
+        assert_eq!(state.spawn_food(&mut rng), None);
+    }
+
+    #[test]
+    fn food_stops_at_the_cap() {
+        let mut state = GameState::default().with_food(FoodConfig {
+            max_food: 2,
+            ..FoodConfig::default()
+        });
+        let mut rng = GameRng::new(5);
+
+        assert!(state.spawn_food(&mut rng).is_some());
+        assert_eq!(state.refill_food(&mut rng).len(), 1);
+        assert_eq!(state.spawn_food(&mut rng), None);
+        assert_eq!(state.food.len(), 2);
+    }
+
+    #[test]
+    fn interval_food_spawns_only_on_matching_ticks() {
+        let mut state = GameState::default().with_food(FoodConfig {
+            max_food: 10,
+            spawn: FoodSpawn::Interval(3),
+        });
+        let mut rng = GameRng::new(8);
+
+        let spawned: Vec<usize> = (0..7)
+            .map(|_| {
+                state.step(&[]);
+                state.spawn_due_food(&mut rng).len()
+            })
+            .collect();
+
+        assert_eq!(spawned, vec![0, 0, 1, 0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn food_placement_is_reproducible_from_the_seed() {
+        let place = |seed| {
+            let mut state = GameState::default().with_food(FoodConfig {
+                max_food: 10,
+                ..FoodConfig::default()
+            });
+            state.refill_food(&mut GameRng::new(seed))
+        };
+
+        assert_eq!(place(99), place(99));
+        assert_ne!(place(99), place(100));
+    }
+
+    #[test]
+    fn checksum_tracks_state_changes() {
+        let mut a = GameState::default();
+        let mut b = GameState::default();
+        assert_eq!(a.checksum(), b.checksum());
+
+        a.step(&[Some(Direction::Right), None]);
+        b.step(&[None, None]);
+        assert_ne!(a.checksum(), b.checksum());
+
+        b = a.clone();
+        assert_eq!(a.checksum(), b.checksum());
+    }
+}
@@ -1,101 +1,166 @@
-use std::collections::HashMap;
+use std::collections::VecDeque;
 
 use bevy::prelude::*;
 
 use crate::{
     components::{self, Direction, GameEndEvent, Player, Position, Size},
     food::Food,
-    grid::{GRID_HEIGHT, GRID_WIDTH},
+    replay::ReplayRecorder,
+    simulation::{GameEvent, GameState, Snake},
 };
 
 const SNAKE_HEAD_COLOR: Color = Color::rgb(0.7, 0.7, 0.7);
-const SNAKE1_SEGMENT_COLOR: Color = Color::rgb(0.8, 0.0, 0.8);
-const SNAKE2_SEGMENT_COLOR: Color = Color::rgb(0., 0.8, 0.8);
+const SNAKE_SEGMENT_COLORS: [Color; 4] = [
+    Color::rgb(0.8, 0.0, 0.8),
+    Color::rgb(0., 0.8, 0.8),
+    Color::rgb(0.8, 0.8, 0.),
+    Color::rgb(0.9, 0.4, 0.),
+];
+const MAX_BUFFERED_TURNS: usize = 2;
 
 #[derive(Component)]
 pub struct Head {
+    /// The direction the snake last moved in.
     direction: Direction,
+    turns: TurnQueue,
+}
+
+/// Turns pressed between ticks, consumed one per movement tick.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TurnQueue(VecDeque<Direction>);
+
+impl TurnQueue {
+    /// Buffers `turn` unless the queue is full, or it would repeat or reverse the turn before it.
+    /// An empty queue checks against `last_moved`, the direction of the last tick.
+    pub fn push(&mut self, last_moved: Direction, turn: Direction) -> bool {
+        let previous = self.0.back().copied().unwrap_or(last_moved);
+        if self.0.len() >= MAX_BUFFERED_TURNS || turn == previous || turn == previous.opposite() {
+            return false;
+        }
+        self.0.push_back(turn);
+        true
+    }
+
+    pub fn pop(&mut self) -> Option<Direction> {
+        self.0.pop_front()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Head {
+    #[must_use]
+    pub const fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Drops any buffered turn in favour of `turn`. Controllers steer this way once per tick.
+    pub fn steer(&mut self, turn: Direction) {
+        self.turns.clear();
+        self.turns.push(self.direction, turn);
+    }
 }
 
 #[derive(Component)]
 pub struct Segment;
 
 #[derive(Default, Deref, DerefMut, Resource)]
-pub struct Segments([Vec<Entity>; 2]);
+pub struct Segments(Vec<Vec<Entity>>);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerConfig {
+    pub color: Color,
+}
+
+/// Per-player settings, indexed by `Player::id`.
+#[derive(Clone, Debug, PartialEq, Deref, DerefMut, Resource)]
+pub struct PlayerConfigs(pub Vec<PlayerConfig>);
+
+impl Default for PlayerConfigs {
+    fn default() -> Self {
+        Self::new(2)
+    }
+}
+
+impl PlayerConfigs {
+    /// Colors cycle every four players.
+    #[must_use]
+    pub fn new(players: u8) -> Self {
+        Self(
+            (0..usize::from(players))
+                .map(|index| PlayerConfig {
+                    color: SNAKE_SEGMENT_COLORS[index % SNAKE_SEGMENT_COLORS.len()],
+                })
+                .collect(),
+        )
+    }
+
+    #[must_use]
+    pub fn color(&self, player_id: u8) -> Color {
+        let index = usize::from(player_id);
+        self.get(index).map_or(
+            SNAKE_SEGMENT_COLORS[index % SNAKE_SEGMENT_COLORS.len()],
+            |config| config.color,
+        )
+    }
+}
 
 pub struct GrowthEvent {
     pub player_id: u8,
 }
 
-#[derive(Default, Resource)]
-pub struct LastTailPosition(Option<Position>);
-
 impl Default for Head {
     fn default() -> Self {
         Self {
             direction: Direction::Up,
+            turns: TurnQueue::default(),
         }
     }
 }
 
-pub fn spawn_system(mut commands: Commands, mut segments: ResMut<Segments>) {
-    *segments = Segments([
-        spawn_entity_with_segment(&mut commands, 0),
-        spawn_entity_with_segment(&mut commands, 1),
-    ]);
+#[allow(clippy::needless_pass_by_value)]
+pub fn spawn_system(
+    mut commands: Commands,
+    mut segments: ResMut<Segments>,
+    state: Res<GameState>,
+    players: Res<PlayerConfigs>,
+) {
+    *segments = spawn_snakes(&mut commands, &state, &players);
 }
 
-fn spawn_entity_with_segment(commands: &mut Commands, player_id: u8) -> Vec<Entity> {
-    vec![
-        commands
-            .spawn(SpriteBundle {
-                sprite: Sprite {
-                    color: SNAKE_HEAD_COLOR,
-                    ..default()
-                },
-                transform: Transform {
-                    scale: Vec3::new(10.0, 10.0, 10.0),
-                    ..default()
-                },
-                ..default()
-            })
-            .insert(components::Player { id: player_id })
-            .insert(Head::default())
-            .insert(Segment)
-            .insert(Position {
-                x: if player_id == 0 {
-                    3
-                } else {
-                    (GRID_WIDTH - 3) as i16
-                },
-                y: 3,
-            })
-            .insert(Size::square(0.8))
-            .id(),
-        spawn_segment_system(
-            commands,
-            Position {
-                x: if player_id == 0 {
-                    3
-                } else {
-                    (GRID_WIDTH - 3) as i16
-                },
-                y: 2,
-            },
-            player_id,
-        ),
-    ]
+/// Spawns every snake in `state` with its whole body, returning the new segments.
+pub fn spawn_snakes(
+    commands: &mut Commands,
+    state: &GameState,
+    players: &PlayerConfigs,
+) -> Segments {
+    Segments(
+        state
+            .snakes
+            .iter()
+            .map(|snake| spawn_entity_with_segment(commands, snake, players.color(snake.player.id)))
+            .collect(),
+    )
 }
 
-pub fn spawn_segment_system(commands: &mut Commands, position: Position, player_id: u8) -> Entity {
-    commands
+fn spawn_entity_with_segment(commands: &mut Commands, snake: &Snake, color: Color) -> Vec<Entity> {
+    let player_id = snake.player.id;
+    let head = commands
         .spawn(SpriteBundle {
             sprite: Sprite {
-                color: if player_id == 0 {
-                    SNAKE1_SEGMENT_COLOR
-                } else {
-                    SNAKE2_SEGMENT_COLOR
-                },
+                color: SNAKE_HEAD_COLOR,
                 ..default()
             },
             transform: Transform {
@@ -104,152 +169,137 @@ pub fn spawn_segment_system(commands: &mut Commands, position: Position, player_
             },
             ..default()
         })
+        .insert(components::Player { id: player_id })
+        .insert(Head {
+            direction: snake.direction,
+            turns: TurnQueue::default(),
+        })
+        .insert(Segment)
+        .insert(snake.head().clone())
+        .insert(Size::square(0.8))
+        .id();
+
+    std::iter::once(head)
+        .chain(
+            snake
+                .body
+                .iter()
+                .skip(1)
+                .map(|position| spawn_segment_system(commands, position.clone(), color)),
+        )
+        .collect()
+}
+
+pub fn spawn_segment_system(commands: &mut Commands, position: Position, color: Color) -> Entity {
+    commands
+        .spawn(SpriteBundle {
+            sprite: Sprite { color, ..default() },
+            transform: Transform {
+                scale: Vec3::new(10.0, 10.0, 10.0),
+                ..default()
+            },
+            ..default()
+        })
         .insert(Segment)
         .insert(position)
         .insert(Size::square(0.65))
         .id()
 }
 
+#[allow(clippy::needless_pass_by_value)]
 pub fn movement_system(
-    segments: ResMut<Segments>,
-    mut last_tail_position: ResMut<LastTailPosition>,
+    segments: Res<Segments>,
+    mut state: ResMut<GameState>,
     mut game_end_writer: EventWriter<GameEndEvent>,
-    heads: Query<(Entity, &Head, &Player)>,
-    mut positions: Query<(Entity, &Segment, &mut Position)>,
-    game_end: Query<&GameEndEvent>,
+    recorder: Option<ResMut<ReplayRecorder>>,
+    mut heads: Query<(&mut Head, &Player)>,
+    mut positions: Query<&mut Position, With<Segment>>,
 ) {
-    let positions_clone: HashMap<Entity, Position> = positions
-        .iter()
-        .map(|(entity, _segment, position)| (entity, position.clone()))
-        .collect();
-    for (entity_id, head, Player { id }) in heads.iter() {
-        let player_id = (*id) as usize;
-        (*segments[player_id]).windows(2).for_each(|entity| {
-            if let Ok((_, _segment, mut position)) = positions.get_mut(entity[1]) {
-                if let Some(new_position) = positions_clone.get(&entity[0]) {
-                    *position = new_position.clone();
-                }
-            };
-        });
-        if game_end.is_empty() {
-            let _ = positions.get_mut(entity_id).map(|(_, _segment, mut pos)| {
-                match &head.direction {
-                    Direction::Left => {
-                        pos.x -= 1;
-                    }
-                    Direction::Right => {
-                        pos.x += 1;
-                    }
-                    Direction::Up => {
-                        pos.y += 1;
-                    }
-                    Direction::Down => {
-                        pos.y -= 1;
-                    }
-                };
-                if pos.x < 0
-                    || pos.y < 0
-                    || pos.x as u16 >= GRID_WIDTH
-                    || pos.y as u16 >= GRID_HEIGHT
-                {
-                    game_end_writer.send(GameEndEvent::GameOver);
-                }
+    let mut inputs = vec![None; state.snakes.len()];
+    for (mut head, player) in &mut heads {
+        if let Some(input) = inputs.get_mut(player.id()) {
+            *input = head.turns.pop();
+        }
+    }
+    if let Some(replay) = recorder.and_then(|recorder| recorder.into_inner().replay.as_mut()) {
+        replay.record(&inputs);
+    }
 
-                if positions_clone
-                    .iter()
-                    .filter(|(k, _)| k != &&entity_id)
-                    .map(|(_, v)| v)
-                    .any(|segment_position| &*pos == segment_position)
-                {
-                    game_end_writer.send(GameEndEvent::GameOver);
-                }
-            });
+    for event in state.move_snakes(&inputs) {
+        if let GameEvent::GameEnd(game_end) = event {
+            game_end_writer.send(game_end);
+        }
+    }
+
+    for (mut head, player) in &mut heads {
+        if let Some(snake) = state.snakes.get(player.id()) {
+            head.direction = snake.direction;
+        }
+    }
+    for (entities, snake) in segments.iter().zip(&state.snakes) {
+        for (entity, new_position) in entities.iter().zip(&snake.body) {
+            if let Ok(mut position) = positions.get_mut(*entity) {
+                *position = new_position.clone();
+            }
         }
-        *last_tail_position = LastTailPosition(Some(
-            positions_clone
-                .get(segments[player_id].last().unwrap())
-                .unwrap()
-                .clone(),
-        ));
     }
 }
 
 pub fn eating_system(
     mut commands: Commands,
+    mut state: ResMut<GameState>,
     mut growth_writer: EventWriter<GrowthEvent>,
     food_positions: Query<(Entity, &Position), With<Food>>,
-    head_positions: Query<(&Position, &Player), With<Head>>,
 ) {
-    for (head_pos, Player { id }) in head_positions.iter() {
-        for (ent, food_pos) in food_positions.iter() {
-            if food_pos == head_pos {
-                commands.entity(ent).despawn();
-                growth_writer.send(GrowthEvent { player_id: *id });
-            }
+    for event in state.eat() {
+        if let GameEvent::Ate {
+            player_id,
+            position,
+        } = event
+        {
+            food_positions
+                .iter()
+                .filter(|(_, food_position)| **food_position == position)
+                .for_each(|(ent, _)| commands.entity(ent).despawn());
+            growth_writer.send(GrowthEvent { player_id });
         }
     }
 }
 
+#[allow(clippy::needless_pass_by_value)]
 pub fn growth_system(
     mut commands: Commands,
-    last_tail_position: Res<LastTailPosition>,
+    mut state: ResMut<GameState>,
     mut segments: ResMut<Segments>,
+    players: Res<PlayerConfigs>,
     mut growth_reader: EventReader<GrowthEvent>,
 ) {
     growth_reader.iter().for_each(|event| {
         let player_id = event.player_id as usize;
         if player_id < segments.len() {
-            segments[player_id].push(spawn_segment_system(
-                &mut commands,
-                last_tail_position.0.clone().unwrap(),
-                event.player_id,
-            ));
-        }
-    });
-}
-
-#[allow(clippy::needless_pass_by_value)]
-pub fn movement_input_system(
-    keyboard_input: Res<Input<KeyCode>>,
-    mut heads: Query<(&mut Head, &Player)>,
-) {
-    heads.iter_mut().for_each(|(mut head, player)| {
-        let dir: Direction = if player.id() == 0 {
-            if keyboard_input.pressed(KeyCode::A) {
-                Direction::Left
-            } else if keyboard_input.pressed(KeyCode::S) {
-                Direction::Down
-            } else if keyboard_input.pressed(KeyCode::W) {
-                Direction::Up
-            } else if keyboard_input.pressed(KeyCode::D) {
-                Direction::Right
-            } else {
-                head.direction
-            }
-        } else if player.id() == 1 {
-            if keyboard_input.pressed(KeyCode::Left) {
-                Direction::Left
-            } else if keyboard_input.pressed(KeyCode::Down) {
-                Direction::Down
-            } else if keyboard_input.pressed(KeyCode::Up) {
-                Direction::Up
-            } else if keyboard_input.pressed(KeyCode::Right) {
-                Direction::Right
-            } else {
-                head.direction
+            if let Some(position) = state.grow(event.player_id) {
+                segments[player_id].push(spawn_segment_system(
+                    &mut commands,
+                    position,
+                    players.color(event.player_id),
+                ));
             }
-        } else {
-            head.direction
-        };
-        if dir != head.direction.opposite() {
-            head.direction = dir;
         }
     });
 }
 
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
+
     use super::*;
+    use crate::{
+        bindings::InputBindings,
+        controller::{controller_input_system, controller_system},
+        grid::BoardConfig,
+        simulation::SpawnPoint,
+        tick::{self, GameTick, TickTimer},
+    };
 
     #[test]
     fn entity_has_snake_head() {
@@ -258,6 +308,8 @@ mod test {
 
         // Add startup system
         app.insert_resource(Segments::default())
+            .insert_resource(GameState::default())
+            .init_resource::<PlayerConfigs>()
             .add_startup_system(spawn_system);
 
         // Run systems
@@ -274,6 +326,8 @@ mod test {
 
         // Add startup system
         app.insert_resource(Segments::default())
+            .insert_resource(GameState::default())
+            .init_resource::<PlayerConfigs>()
             .add_startup_system(spawn_system);
 
         // Run systems
@@ -289,18 +343,21 @@ mod test {
         // Setup
         let mut app = App::new();
         let p1_default_position = Position { x: 3, y: 4 };
-        #[cfg(debug_assertions)]
-        let p2_default_position = Position { x: 7, y: 4 };
-        #[cfg(not(debug_assertions))]
         let p2_default_position = Position { x: 17, y: 4 };
 
         // Add systems
         app.insert_resource(Segments::default())
-            .insert_resource(LastTailPosition::default())
+            .insert_resource(GameState::default())
+            .init_resource::<PlayerConfigs>()
+            .insert_resource(InputBindings::default().controllers())
             .add_event::<GameEndEvent>()
             .add_startup_system(spawn_system)
             .add_system(movement_system)
-            .add_system(movement_input_system.before(movement_system));
+            .add_systems(
+                (controller_input_system, controller_system)
+                    .chain()
+                    .before(movement_system),
+            );
 
         // Add input resource
         let mut input = Input::<KeyCode>::default();
@@ -328,18 +385,21 @@ mod test {
         // Setup
         let mut app = App::new();
         let p1_up_position = Position { x: 3, y: 4 };
-        #[cfg(debug_assertions)]
-        let p2_up_position = Position { x: 7, y: 4 };
-        #[cfg(not(debug_assertions))]
         let p2_up_position = Position { x: 17, y: 4 };
 
         // Add systems
         app.insert_resource(Segments::default())
-            .insert_resource(LastTailPosition::default())
+            .insert_resource(GameState::default())
+            .init_resource::<PlayerConfigs>()
+            .insert_resource(InputBindings::default().controllers())
             .add_event::<GameEndEvent>()
             .add_startup_system(spawn_system)
             .add_system(movement_system)
-            .add_system(movement_input_system.before(movement_system));
+            .add_systems(
+                (controller_input_system, controller_system)
+                    .chain()
+                    .before(movement_system),
+            );
 
         // Move Up
         let mut input = Input::<KeyCode>::default();
@@ -359,9 +419,6 @@ mod test {
             });
 
         let p1_up_right_position = Position { x: 4, y: 4 };
-        #[cfg(debug_assertions)]
-        let p2_up_right_position = Position { x: 7, y: 5 };
-        #[cfg(not(debug_assertions))]
         let p2_up_right_position = Position { x: 17, y: 5 };
 
         // Move Right
@@ -389,18 +446,21 @@ mod test {
         // Setup
         let mut app = App::new();
         let down_left_position = Position { x: 2, y: 2 };
-        #[cfg(debug_assertions)]
-        let p2_up_position = Position { x: 7, y: 5 };
-        #[cfg(not(debug_assertions))]
         let p2_up_position = Position { x: 17, y: 5 };
 
         // Add systems
         app.insert_resource(Segments::default())
-            .insert_resource(LastTailPosition::default())
+            .insert_resource(GameState::default())
+            .init_resource::<PlayerConfigs>()
+            .insert_resource(InputBindings::default().controllers())
             .add_event::<GameEndEvent>()
             .add_startup_system(spawn_system)
             .add_system(movement_system)
-            .add_system(movement_input_system.before(movement_system));
+            .add_systems(
+                (controller_input_system, controller_system)
+                    .chain()
+                    .before(movement_system),
+            );
 
         // Move Left
         let mut input = Input::<KeyCode>::default();
@@ -434,18 +494,21 @@ mod test {
         // Setup
         let mut app = App::new();
         let p1_down_left_position = Position { x: 3, y: 4 };
-        #[cfg(debug_assertions)]
-        let p2_down_left_position = Position { x: 7, y: 4 };
-        #[cfg(not(debug_assertions))]
         let p2_down_left_position = Position { x: 17, y: 4 };
 
         // Add systems
         app.insert_resource(Segments::default())
-            .insert_resource(LastTailPosition::default())
+            .insert_resource(GameState::default())
+            .init_resource::<PlayerConfigs>()
+            .insert_resource(InputBindings::default().controllers())
             .add_event::<GameEndEvent>()
             .add_startup_system(spawn_system)
             .add_system(movement_system)
-            .add_system(movement_input_system.before(movement_system));
+            .add_systems(
+                (controller_input_system, controller_system)
+                    .chain()
+                    .before(movement_system),
+            );
 
         // Move down
         let mut input = Input::<KeyCode>::default();
@@ -473,6 +536,8 @@ mod test {
 
         // Add startup system
         app.insert_resource(Segments::default())
+            .insert_resource(GameState::default())
+            .init_resource::<PlayerConfigs>()
             .add_startup_system(spawn_system);
 
         // Run systems
@@ -492,11 +557,17 @@ mod test {
 
         // Add systems
         app.insert_resource(Segments::default())
-            .insert_resource(LastTailPosition::default())
+            .insert_resource(GameState::default())
+            .init_resource::<PlayerConfigs>()
+            .insert_resource(InputBindings::default().controllers())
             .add_event::<GameEndEvent>()
             .add_startup_system(spawn_system)
             .add_system(movement_system)
-            .add_system(movement_input_system.before(movement_system));
+            .add_systems(
+                (controller_input_system, controller_system)
+                    .chain()
+                    .before(movement_system),
+            );
 
         // Add input resource
         let mut input = Input::<KeyCode>::default();
@@ -566,17 +637,22 @@ mod test {
 
         // Add systems
         app.insert_resource(Segments::default())
-            .insert_resource(LastTailPosition::default())
+            .insert_resource(GameState {
+                food: vec![Position { x: 3, y: 5 }],
+                ..default()
+            })
+            .init_resource::<PlayerConfigs>()
             .add_event::<GrowthEvent>()
             .add_event::<GameEndEvent>()
             .add_startup_system(spawn_system)
-            .add_system(crate::food::spawn_system)
             .add_systems((
                 movement_system,
                 eating_system.after(movement_system),
                 growth_system.after(eating_system),
             ));
 
+        app.world.spawn((Food, Position { x: 3, y: 5 }));
+
         // Run systems
         app.update();
 
@@ -589,5 +665,205 @@ mod test {
 
         let mut query = app.world.query::<(&Segment, &Position)>();
         assert_eq!(query.iter(&app.world).count(), 5);
+        let mut query = app.world.query::<(&Food, &Position)>();
+        assert_eq!(query.iter(&app.world).count(), 0);
+    }
+
+    #[test]
+    fn snakes_eating_in_the_same_tick_grow_at_their_own_tails() {
+        // Setup
+        let mut app = App::new();
+        let p2_x = 17;
+        let state = GameState {
+            food: vec![Position { x: 3, y: 4 }, Position { x: p2_x, y: 4 }],
+            ..default()
+        };
+
+        // Add systems
+        app.insert_resource(Segments::default())
+            .insert_resource(state)
+            .init_resource::<PlayerConfigs>()
+            .add_event::<GrowthEvent>()
+            .add_event::<GameEndEvent>()
+            .add_startup_system(spawn_system)
+            .add_systems((
+                movement_system,
+                eating_system.after(movement_system),
+                growth_system.after(eating_system),
+            ));
+
+        // Run systems
+        app.update();
+
+        let segments = app.world.resource::<Segments>();
+        assert_eq!(segments[0].len(), 3);
+        assert_eq!(segments[1].len(), 3);
+        let (p1_tail, p2_tail) = (segments[0][2], segments[1][2]);
+
+        assert_eq!(
+            app.world.get::<Position>(p1_tail),
+            Some(&Position { x: 3, y: 2 })
+        );
+        assert_eq!(
+            app.world.get::<Position>(p2_tail),
+            Some(&Position { x: p2_x, y: 2 })
+        );
+    }
+
+    #[test]
+    fn third_player_spawns_and_steers_with_its_own_keys() {
+        // Setup
+        let mut app = App::new();
+
+        // Add systems
+        app.insert_resource(Segments::default())
+            .insert_resource(GameState::new(BoardConfig::default(), 3))
+            .insert_resource(PlayerConfigs::new(3))
+            .insert_resource(InputBindings::new(3).controllers())
+            .add_event::<GameEndEvent>()
+            .add_startup_system(spawn_system)
+            .add_system(movement_system)
+            .add_systems(
+                (controller_input_system, controller_system)
+                    .chain()
+                    .before(movement_system),
+            );
+
+        // Add input resource
+        let mut input = Input::<KeyCode>::default();
+        input.press(KeyCode::L);
+        app.insert_resource(input);
+
+        // Run systems
+        app.update();
+
+        assert_eq!(app.world.resource::<Segments>().len(), 3);
+        let mut query = app.world.query::<(&Head, &Position, &Player)>();
+        query
+            .iter(&app.world)
+            .for_each(|(head, position, Player { id })| match id {
+                0 => assert_eq!(position, &Position { x: 3, y: 4 }),
+                1 => assert_eq!(position, &Position { x: 17, y: 4 }),
+                _ => {
+                    assert_eq!(position, &Position { x: 4, y: 16 });
+                    assert_eq!(head.direction, Direction::Right);
+                }
+            });
+    }
+
+    #[test]
+    fn turn_queue_rejects_reversals_and_overflow() {
+        let mut turns = TurnQueue::default();
+
+        assert!(!turns.push(Direction::Right, Direction::Left));
+        assert!(!turns.push(Direction::Right, Direction::Right));
+        assert!(turns.push(Direction::Right, Direction::Up));
+        assert!(!turns.push(Direction::Right, Direction::Down));
+        assert!(turns.push(Direction::Right, Direction::Left));
+        assert!(!turns.push(Direction::Right, Direction::Down));
+
+        assert_eq!(turns.pop(), Some(Direction::Up));
+        assert_eq!(turns.pop(), Some(Direction::Left));
+        assert_eq!(turns.pop(), None);
+    }
+
+    #[test]
+    fn held_keys_turn_once_and_keep_going() {
+        // Setup: one snake moving up
+        let mut app = App::new();
+        app.insert_resource(Segments::default())
+            .insert_resource(GameState::with_spawn_points(
+                BoardConfig::default(),
+                [SpawnPoint {
+                    head: Position { x: 5, y: 5 },
+                    direction: Direction::Up,
+                }],
+            ))
+            .init_resource::<PlayerConfigs>()
+            .insert_resource(InputBindings::default().controllers())
+            .init_resource::<TickTimer>()
+            .add_event::<GameEndEvent>()
+            .add_startup_system(spawn_system)
+            .configure_set(GameTick.run_if(tick::tick_finished))
+            .add_system(controller_input_system.before(GameTick))
+            .add_systems(
+                (controller_system, movement_system)
+                    .chain()
+                    .in_set(GameTick),
+            );
+
+        // Hold Right for a few frames between ticks
+        let mut input = Input::<KeyCode>::default();
+        input.press(KeyCode::D);
+        app.insert_resource(input);
+        for _ in 0..3 {
+            app.update();
+            app.world.resource_mut::<Input<KeyCode>>().clear();
+        }
+        for _ in 0..2 {
+            app.world
+                .resource_mut::<TickTimer>()
+                .tick(Duration::from_secs_f32(0.15));
+            app.update();
+        }
+
+        let mut query = app.world.query::<(&Head, &Position)>();
+        let (head, position) = query.single(&app.world);
+        assert_eq!(position, &Position { x: 7, y: 5 });
+        assert_eq!(head.direction(), Direction::Right);
+    }
+
+    #[test]
+    fn quick_turns_between_ticks_are_buffered_instead_of_reversing() {
+        // Setup: one snake moving right
+        let mut app = App::new();
+        let board = BoardConfig::default();
+        app.insert_resource(Segments::default())
+            .insert_resource(GameState::with_spawn_points(
+                board,
+                [SpawnPoint {
+                    head: Position { x: 5, y: 5 },
+                    direction: Direction::Right,
+                }],
+            ))
+            .init_resource::<PlayerConfigs>()
+            .insert_resource(InputBindings::default().controllers())
+            .init_resource::<TickTimer>()
+            .add_event::<GameEndEvent>()
+            .add_startup_system(spawn_system)
+            .configure_set(GameTick.run_if(tick::tick_finished))
+            .add_system(controller_input_system.before(GameTick))
+            .add_systems(
+                (controller_system, movement_system)
+                    .chain()
+                    .in_set(GameTick),
+            );
+        let frame = |app: &mut App, key: Option<KeyCode>, elapsed: f32| {
+            let mut input = Input::<KeyCode>::default();
+            if let Some(key) = key {
+                input.press(key);
+            }
+            app.insert_resource(input);
+            app.world
+                .resource_mut::<TickTimer>()
+                .tick(Duration::from_secs_f32(elapsed));
+            app.update();
+        };
+        let head_position = |app: &mut App| {
+            let mut query = app.world.query_filtered::<&Position, With<Head>>();
+            query.single(&app.world).clone()
+        };
+
+        // Up then Left within the same tick
+        frame(&mut app, Some(KeyCode::W), 0.);
+        frame(&mut app, Some(KeyCode::A), 0.);
+        assert_eq!(head_position(&mut app), Position { x: 5, y: 5 });
+
+        frame(&mut app, None, 0.15);
+        assert_eq!(head_position(&mut app), Position { x: 5, y: 6 });
+
+        frame(&mut app, None, 0.15);
+        assert_eq!(head_position(&mut app), Position { x: 4, y: 6 });
+        assert!(app.world.resource::<GameState>().snakes[0].is_alive());
     }
 }
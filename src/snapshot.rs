@@ -0,0 +1,432 @@
+use std::{fmt, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    controller::Controllers,
+    food::{self, Food},
+    game::AppState,
+    grid::BoardConfig,
+    replay::{ReplayPlayback, ReplayRecorder},
+    rng::GameRng,
+    score::Scoreboard,
+    simulation::GameState,
+    snake::{self, PlayerConfigs, Segment, Segments},
+};
+
+const SAVE_KEY: KeyCode = KeyCode::F5;
+const LOAD_KEY: KeyCode = KeyCode::F9;
+
+/// A game in progress, complete enough to carry on exactly where it was saved.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Board config, every snake's segments from head to tail and direction, food and tick.
+    /// `Segments` always mirrors the snake bodies, so they are not stored separately.
+    pub state: GameState,
+    pub scoreboard: Scoreboard,
+    /// Generator state at the time of saving, so food keeps spawning in the same places.
+    pub rng: GameRng,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    /// Saved on a board of a different size or with different edges.
+    Board {
+        saved: BoardConfig,
+        current: BoardConfig,
+    },
+    /// Saved with a different number of snakes.
+    Players {
+        saved: usize,
+        current: usize,
+    },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "could not access snapshot: {error}"),
+            Self::Parse(error) => write!(f, "invalid snapshot: {error}"),
+            Self::Serialize(error) => write!(f, "could not write snapshot: {error}"),
+            Self::Board { saved, current } => write!(
+                f,
+                "snapshot is of a {}x{} board with {:?} edges, this game is {}x{} with {:?}",
+                saved.width,
+                saved.height,
+                saved.edges,
+                current.width,
+                current.height,
+                current.edges
+            ),
+            Self::Players { saved, current } => {
+                write!(f, "snapshot has {saved} snakes, this game has {current}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl Snapshot {
+    #[must_use]
+    pub fn capture(state: &GameState, scoreboard: &Scoreboard, rng: &GameRng) -> Self {
+        Self {
+            state: state.clone(),
+            scoreboard: scoreboard.clone(),
+            rng: rng.clone(),
+        }
+    }
+
+    pub fn to_ron(&self) -> Result<String, SnapshotError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(SnapshotError::Serialize)
+    }
+
+    pub fn from_ron(source: &str) -> Result<Self, SnapshotError> {
+        ron::from_str(source).map_err(SnapshotError::Parse)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        std::fs::write(path, self.to_ron()?).map_err(SnapshotError::Io)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SnapshotError> {
+        Self::from_ron(&std::fs::read_to_string(path).map_err(SnapshotError::Io)?)
+    }
+
+    /// The window, controls and HUD are set up for one board and player count, so snapshots
+    /// only load into a game that matches both.
+    pub fn check_fits(&self, board: &BoardConfig, players: usize) -> Result<(), SnapshotError> {
+        if self.state.board != *board {
+            return Err(SnapshotError::Board {
+                saved: self.state.board,
+                current: *board,
+            });
+        }
+        if self.state.snakes.len() != players {
+            return Err(SnapshotError::Players {
+                saved: self.state.snakes.len(),
+                current: players,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Where the save and load hotkeys keep their snapshot.
+#[derive(Resource, Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotPath(pub String);
+
+impl Default for SnapshotPath {
+    fn default() -> Self {
+        Self("snapshot.ron".to_string())
+    }
+}
+
+/// Finished games have nothing left to continue, so snapshots only cover games in progress.
+const fn in_game(app_state: AppState) -> bool {
+    matches!(app_state, AppState::Playing | AppState::Paused)
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn save_snapshot_system(
+    keyboard_input: Res<Input<KeyCode>>,
+    app_state: Res<State<AppState>>,
+    path: Res<SnapshotPath>,
+    state: Res<GameState>,
+    scoreboard: Res<Scoreboard>,
+    rng: Res<GameRng>,
+) {
+    if !keyboard_input.just_pressed(SAVE_KEY) || !in_game(app_state.0) {
+        return;
+    }
+    match Snapshot::capture(&state, &scoreboard, &rng).save(&path.0) {
+        Ok(()) => println!("Game saved to {}", path.0),
+        Err(error) => eprintln!("{error}"),
+    }
+}
+
+/// Swaps the current game for the saved one and pauses, so nobody is caught off guard.
+///
+/// Replays follow one game from its start, so nothing is loaded while recording or playing one.
+#[allow(
+    clippy::needless_pass_by_value,
+    clippy::too_many_arguments,
+    clippy::type_complexity
+)]
+pub fn load_snapshot_system(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    app_state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    path: Res<SnapshotPath>,
+    board: Res<BoardConfig>,
+    players: Res<PlayerConfigs>,
+    recorder: Option<Res<ReplayRecorder>>,
+    playback: Option<Res<ReplayPlayback>>,
+    mut controllers: ResMut<Controllers>,
+    mut state: ResMut<GameState>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut rng: ResMut<GameRng>,
+    mut segments: ResMut<Segments>,
+    entities: Query<Entity, Or<(With<Segment>, With<Food>)>>,
+) {
+    if !keyboard_input.just_pressed(LOAD_KEY) || !in_game(app_state.0) {
+        return;
+    }
+    if recorder.is_some() || playback.is_some() {
+        eprintln!("Snapshots cannot be loaded during a replay or recording");
+        return;
+    }
+    let snapshot = match Snapshot::load(&path.0).and_then(|snapshot| {
+        snapshot
+            .check_fits(&board, players.len())
+            .map(|()| snapshot)
+    }) {
+        Ok(snapshot) => snapshot,
+        Err(error) => {
+            eprintln!("{error}");
+            return;
+        }
+    };
+
+    entities.for_each(|entity| commands.entity(entity).despawn());
+    *segments = snake::spawn_snakes(&mut commands, &snapshot.state, &players);
+    for position in &snapshot.state.food {
+        food::spawn_food_entity(&mut commands, position.clone());
+    }
+    *state = snapshot.state;
+    *scoreboard = snapshot.scoreboard;
+    *rng = snapshot.rng;
+    // Buffered turns and pending requests belong to the game that was just replaced.
+    controllers.restart();
+
+    println!("Game loaded from {}, press P to continue", path.0);
+    next_state.set(AppState::Paused);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        bindings::InputBindings,
+        bot::{BotController, BotLevel},
+        components::{Player, Position},
+        controller,
+        food::{FoodConfig, FoodSpawn},
+        grid::BoardConfig,
+        snake::Head,
+    };
+
+    fn bots() -> Controllers {
+        Controllers::new(2)
+            .with(0, BotController(BotLevel::Safe))
+            .with(1, BotController(BotLevel::LookAhead))
+    }
+
+    fn tick(state: &mut GameState, rng: &mut GameRng, controllers: &mut Controllers) {
+        let inputs = controllers.decide(state);
+        state.step(&inputs);
+        state.spawn_due_food(rng);
+    }
+
+    fn snapshot_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("bevy-snake-{}-{name}.ron", std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn snapshot_app(path: &str, state: GameState) -> App {
+        let mut app = App::new();
+        app.add_state::<AppState>()
+            .insert_resource(SnapshotPath(path.to_string()))
+            .insert_resource(state.board)
+            .insert_resource(PlayerConfigs::new(state.snakes.len() as u8))
+            .insert_resource(Controllers::new(state.snakes.len() as u8))
+            .insert_resource(Scoreboard::from_state(&state))
+            .insert_resource(state)
+            .insert_resource(GameRng::new(1))
+            .insert_resource(Segments::default())
+            .init_resource::<Input<KeyCode>>()
+            .add_startup_system(snake::spawn_system)
+            .add_systems((save_snapshot_system, load_snapshot_system));
+        app.world
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Playing);
+        app.update();
+        app
+    }
+
+    fn press(app: &mut App, key: KeyCode) {
+        let mut input = Input::<KeyCode>::default();
+        input.press(key);
+        app.insert_resource(input);
+        app.update();
+    }
+
+    #[test]
+    fn continuing_from_a_loaded_snapshot_gives_the_same_game() {
+        let mut state = GameState::new(BoardConfig::new(14, 14), 2).with_food(FoodConfig {
+            max_food: 2,
+            spawn: FoodSpawn::Interval(3),
+        });
+        let mut rng = GameRng::new(17);
+        let mut controllers = bots();
+        for _ in 0..25 {
+            tick(&mut state, &mut rng, &mut controllers);
+        }
+        let scoreboard = Scoreboard::from_state(&state);
+
+        let snapshot = Snapshot::capture(&state, &scoreboard, &rng);
+        let mut loaded = Snapshot::from_ron(&snapshot.to_ron().unwrap()).unwrap();
+        assert_eq!(loaded, snapshot);
+
+        let mut loaded_controllers = bots();
+        for _ in 0..40 {
+            tick(&mut state, &mut rng, &mut controllers);
+            tick(&mut loaded.state, &mut loaded.rng, &mut loaded_controllers);
+            assert_eq!(loaded.state, state);
+        }
+        assert_eq!(loaded.rng, rng);
+    }
+
+    #[test]
+    fn hotkeys_save_and_restore_the_game() {
+        let path = snapshot_path("hotkeys");
+        let mut saved = GameState::new(BoardConfig::default(), 2);
+        saved.snakes[0].body.push_back(Position { x: 3, y: 1 });
+        saved.food = vec![Position { x: 9, y: 9 }, Position { x: 1, y: 12 }];
+        saved.tick = 42;
+
+        let mut saving = snapshot_app(&path, saved.clone());
+        press(&mut saving, SAVE_KEY);
+        let mut loading = snapshot_app(&path, GameState::default());
+        press(&mut loading, LOAD_KEY);
+        loading.update();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(*loading.world.resource::<GameState>(), saved);
+        assert_eq!(
+            loading.world.resource::<State<AppState>>().0,
+            AppState::Paused
+        );
+        let segments: Vec<usize> = loading
+            .world
+            .resource::<Segments>()
+            .iter()
+            .map(Vec::len)
+            .collect();
+        assert_eq!(segments, vec![3, 2]);
+        let mut heads = loading.world.query::<(&Position, &Player, &Head)>();
+        for (position, player, head) in heads.iter(&loading.world) {
+            let snake = &saved.snakes[player.id()];
+            assert_eq!(position, snake.head());
+            assert_eq!(head.direction(), snake.direction);
+        }
+        let mut food = loading
+            .world
+            .query_filtered::<&Position, With<Food>>()
+            .iter(&loading.world)
+            .cloned()
+            .collect::<Vec<_>>();
+        food.sort_by_key(|position| (position.x, position.y));
+        assert_eq!(
+            food,
+            vec![Position { x: 1, y: 12 }, Position { x: 9, y: 9 }]
+        );
+    }
+
+    fn save(path: &str, state: GameState) {
+        let scoreboard = Scoreboard::from_state(&state);
+        Snapshot::capture(&state, &scoreboard, &GameRng::new(3))
+            .save(path)
+            .unwrap();
+    }
+
+    #[test]
+    fn snapshots_of_other_boards_are_rejected() {
+        let path = snapshot_path("other-board");
+        save(&path, GameState::new(BoardConfig::new(14, 14), 2));
+        let mut app = snapshot_app(&path, GameState::default());
+
+        press(&mut app, LOAD_KEY);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(*app.world.resource::<GameState>(), GameState::default());
+        assert_eq!(app.world.resource::<State<AppState>>().0, AppState::Playing);
+    }
+
+    #[test]
+    fn snapshots_with_other_player_counts_are_rejected() {
+        let path = snapshot_path("other-players");
+        let saved = GameState::new(BoardConfig::default(), 3);
+        save(&path, saved.clone());
+        let mut app = snapshot_app(&path, GameState::default());
+
+        press(&mut app, LOAD_KEY);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(*app.world.resource::<GameState>(), GameState::default());
+        assert!(matches!(
+            Snapshot::capture(&saved, &Scoreboard::from_state(&saved), &GameRng::new(3))
+                .check_fits(&BoardConfig::default(), 2),
+            Err(SnapshotError::Players {
+                saved: 3,
+                current: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn snapshots_do_not_load_while_recording() {
+        let path = snapshot_path("recording");
+        let mut saved = GameState::new(BoardConfig::default(), 2);
+        saved.tick = 42;
+        save(&path, saved);
+        let mut app = snapshot_app(&path, GameState::default());
+        app.insert_resource(ReplayRecorder {
+            replay: None,
+            path: None,
+        });
+
+        press(&mut app, LOAD_KEY);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(*app.world.resource::<GameState>(), GameState::default());
+        assert_eq!(app.world.resource::<State<AppState>>().0, AppState::Playing);
+    }
+
+    #[test]
+    fn loading_drops_turns_buffered_for_the_old_game() {
+        let path = snapshot_path("buffered");
+        save(&path, GameState::new(BoardConfig::default(), 2));
+        let mut app = snapshot_app(&path, GameState::default());
+        app.insert_resource(InputBindings::default().controllers())
+            .add_system(controller::controller_input_system.before(load_snapshot_system));
+
+        press(&mut app, KeyCode::D);
+        press(&mut app, LOAD_KEY);
+        std::fs::remove_file(&path).unwrap();
+
+        let state = app.world.resource::<GameState>().clone();
+        let directions = app.world.resource_mut::<Controllers>().decide(&state);
+        assert_eq!(directions, vec![None, None]);
+    }
+
+    #[test]
+    fn missing_snapshots_leave_the_game_alone() {
+        let path = snapshot_path("missing");
+        let mut app = snapshot_app(&path, GameState::default());
+
+        press(&mut app, LOAD_KEY);
+
+        assert_eq!(*app.world.resource::<GameState>(), GameState::default());
+        assert_eq!(app.world.resource::<State<AppState>>().0, AppState::Playing);
+    }
+}
@@ -0,0 +1,199 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{simulation::GameState, tick::TickTimer};
+
+/// What counts as progress when speeding the game up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpeedMetric {
+    #[default]
+    FoodEaten,
+    /// Combined length of every snake on the board.
+    TotalLength,
+}
+
+/// How the tick interval shrinks with progress. Steps are in seconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SpeedCurve {
+    #[default]
+    Constant,
+    /// Shorter by `step` for every unit of progress.
+    Linear { step: f32 },
+    /// Shorter by `step` once every `every` units of progress.
+    Stepped { every: u32, step: f32 },
+    /// Like `Linear`, but stops speeding up after `max` units of progress.
+    Capped { step: f32, max: u32 },
+}
+
+impl SpeedCurve {
+    /// Curve presets by name, as accepted by `--speed`.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "constant" => Some(Self::Constant),
+            "linear" => Some(Self::Linear { step: 0.005 }),
+            "stepped" => Some(Self::Stepped {
+                every: 5,
+                step: 0.02,
+            }),
+            "capped" => Some(Self::Capped {
+                step: 0.005,
+                max: 10,
+            }),
+            _ => None,
+        }
+    }
+
+    fn reduction(self, progress: u32) -> f32 {
+        #[allow(clippy::cast_precision_loss)]
+        match self {
+            Self::Constant => 0.,
+            Self::Linear { step } => progress as f32 * step,
+            Self::Stepped { every, step } => (progress / every.max(1)) as f32 * step,
+            Self::Capped { step, max } => progress.min(max) as f32 * step,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct SpeedProgression {
+    /// Tick interval in seconds at the start of a game.
+    pub base: f32,
+    /// The interval never drops below this many seconds.
+    pub min: f32,
+    pub metric: SpeedMetric,
+    pub curve: SpeedCurve,
+}
+
+impl Default for SpeedProgression {
+    fn default() -> Self {
+        Self {
+            base: 0.15,
+            min: 0.05,
+            metric: SpeedMetric::default(),
+            curve: SpeedCurve::default(),
+        }
+    }
+}
+
+impl SpeedProgression {
+    #[must_use]
+    pub const fn with_curve(mut self, curve: SpeedCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_metric(mut self, metric: SpeedMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    #[must_use]
+    pub fn progress(&self, state: &GameState) -> u32 {
+        match self.metric {
+            SpeedMetric::FoodEaten => state.food_eaten,
+            SpeedMetric::TotalLength => state
+                .snakes
+                .iter()
+                .map(|snake| snake.body.len() as u32)
+                .sum(),
+        }
+    }
+
+    #[must_use]
+    pub fn interval(&self, progress: u32) -> Duration {
+        let seconds = self.base - self.curve.reduction(progress);
+        Duration::from_secs_f32(seconds.max(self.min))
+    }
+
+    #[must_use]
+    pub fn timer(&self) -> TickTimer {
+        TickTimer::from_seconds(self.base)
+    }
+}
+
+/// Keeps the tick interval in line with the current progress, including after a restart.
+#[allow(clippy::needless_pass_by_value)]
+pub fn speed_progression_system(
+    state: Res<GameState>,
+    progression: Res<SpeedProgression>,
+    mut timer: ResMut<TickTimer>,
+) {
+    let interval = progression.interval(progression.progress(&state));
+    if timer.duration() != interval {
+        timer.set_interval(interval);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::components::Position;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn millis(interval: Duration) -> u32 {
+        (interval.as_secs_f32() * 1000.).round() as u32
+    }
+
+    #[test]
+    fn curves_shorten_the_interval_with_progress() {
+        let linear = SpeedProgression::default().with_curve(SpeedCurve::Linear { step: 0.01 });
+        let stepped = SpeedProgression::default().with_curve(SpeedCurve::Stepped {
+            every: 3,
+            step: 0.02,
+        });
+        let capped =
+            SpeedProgression::default().with_curve(SpeedCurve::Capped { step: 0.01, max: 4 });
+
+        let intervals = |progression: SpeedProgression| -> Vec<u32> {
+            [0, 1, 2, 3, 6, 20]
+                .into_iter()
+                .map(|progress| millis(progression.interval(progress)))
+                .collect()
+        };
+
+        assert_eq!(intervals(SpeedProgression::default()), [150; 6]);
+        assert_eq!(intervals(linear), [150, 140, 130, 120, 90, 50]);
+        assert_eq!(intervals(stepped), [150, 150, 150, 130, 110, 50]);
+        assert_eq!(intervals(capped), [150, 140, 130, 120, 110, 110]);
+    }
+
+    #[test]
+    fn progress_follows_the_chosen_metric() {
+        let mut state = GameState {
+            food: vec![Position { x: 3, y: 4 }],
+            ..GameState::default()
+        };
+        state.step(&[None, None]);
+
+        let by_food = SpeedProgression::default();
+        let by_length = SpeedProgression::default().with_metric(SpeedMetric::TotalLength);
+
+        assert_eq!(by_food.progress(&state), 1);
+        assert_eq!(by_length.progress(&state), 5);
+    }
+
+    #[test]
+    fn system_updates_the_tick_timer() {
+        let mut app = App::new();
+        app.insert_resource(GameState {
+            food_eaten: 2,
+            ..GameState::default()
+        })
+        .insert_resource(SpeedProgression::default().with_curve(SpeedCurve::Linear { step: 0.025 }))
+        .insert_resource(TickTimer::from_seconds(0.15))
+        .add_system(speed_progression_system);
+
+        app.update();
+
+        assert_eq!(millis(app.world.resource::<TickTimer>().duration()), 100);
+
+        app.world.resource_mut::<GameState>().restart();
+        app.update();
+
+        assert_eq!(millis(app.world.resource::<TickTimer>().duration()), 150);
+    }
+}
@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+/// Systems that make up one step of the game, run together in order when `TickTimer` fires.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GameTick;
+
+#[derive(Resource, Debug, Deref, DerefMut)]
+pub struct TickTimer(pub Timer);
+
+impl Default for TickTimer {
+    fn default() -> Self {
+        Self::from_seconds(0.15)
+    }
+}
+
+impl TickTimer {
+    #[must_use]
+    pub fn from_seconds(seconds: f32) -> Self {
+        Self(Timer::from_seconds(seconds, TimerMode::Repeating))
+    }
+
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.set_duration(interval);
+    }
+}
+
+/// Runs the tick timer at double speed while set.
+#[derive(Resource, Debug, Default)]
+pub struct TickBoost(pub bool);
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn tick_timer_system(
+    time: Res<Time>,
+    boost: Option<Res<TickBoost>>,
+    mut timer: ResMut<TickTimer>,
+) {
+    if boost.is_some_and(|boost| boost.0) {
+        timer.tick(time.delta() * 2);
+    } else {
+        timer.tick(time.delta());
+    }
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub fn tick_finished(timer: Res<TickTimer>) -> bool {
+    timer.just_finished()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        components::GameEndEvent,
+        simulation::GameState,
+        snake::{self, GrowthEvent, PlayerConfigs, Segments},
+    };
+
+    fn advance(app: &mut App, seconds: f32) {
+        let mut time = app.world.resource_mut::<Time>();
+        let last = time.last_update().unwrap_or_else(|| time.startup());
+        if time.last_update().is_none() {
+            // The first update only records the start instant.
+            time.update_with_instant(last);
+        }
+        time.update_with_instant(last + Duration::from_secs_f32(seconds));
+        app.update();
+    }
+
+    fn tick_app(timer: TickTimer) -> App {
+        let mut app = App::new();
+        app.insert_resource(Segments::default())
+            .insert_resource(GameState::default())
+            .init_resource::<PlayerConfigs>()
+            .init_resource::<Time>()
+            .insert_resource(timer)
+            .add_event::<GrowthEvent>()
+            .add_event::<GameEndEvent>()
+            .add_startup_system(snake::spawn_system)
+            .configure_set(GameTick.run_if(tick_finished))
+            .add_system(tick_timer_system.before(GameTick))
+            .add_systems(
+                (
+                    snake::movement_system,
+                    snake::eating_system,
+                    snake::growth_system,
+                )
+                    .chain()
+                    .in_set(GameTick),
+            );
+        app
+    }
+
+    #[test]
+    fn game_only_steps_when_the_tick_timer_fires() {
+        let mut app = tick_app(TickTimer::from_seconds(0.15));
+
+        advance(&mut app, 0.1);
+        assert_eq!(app.world.resource::<GameState>().tick, 0);
+
+        advance(&mut app, 0.1);
+        assert_eq!(app.world.resource::<GameState>().tick, 1);
+
+        advance(&mut app, 0.15);
+        assert_eq!(app.world.resource::<GameState>().tick, 2);
+    }
+
+    #[test]
+    fn tick_rate_can_change_while_running() {
+        let mut app = tick_app(TickTimer::from_seconds(0.15));
+
+        advance(&mut app, 0.2);
+        assert_eq!(app.world.resource::<GameState>().tick, 1);
+
+        app.world
+            .resource_mut::<TickTimer>()
+            .set_interval(Duration::from_secs_f32(1.0));
+        advance(&mut app, 0.5);
+        assert_eq!(app.world.resource::<GameState>().tick, 1);
+
+        advance(&mut app, 0.6);
+        assert_eq!(app.world.resource::<GameState>().tick, 2);
+    }
+
+    #[test]
+    fn boost_runs_the_timer_at_double_speed() {
+        let mut app = tick_app(TickTimer::from_seconds(0.15));
+        app.insert_resource(TickBoost(true));
+
+        advance(&mut app, 0.1);
+        assert_eq!(app.world.resource::<GameState>().tick, 1);
+    }
+
+    #[test]
+    fn eating_and_growth_resolve_in_the_same_tick_as_movement() {
+        let mut app = tick_app(TickTimer::from_seconds(0.15));
+        app.world.resource_mut::<GameState>().food =
+            vec![crate::components::Position { x: 3, y: 4 }];
+
+        advance(&mut app, 0.2);
+
+        assert_eq!(app.world.resource::<GameState>().snakes[0].body.len(), 3);
+        assert_eq!(app.world.resource::<Segments>()[0].len(), 3);
+    }
+}
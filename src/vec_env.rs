@@ -0,0 +1,242 @@
+use rayon::prelude::*;
+
+use crate::{
+    components::Direction,
+    controller::Controllers,
+    env::{EnvConfig, Observation, SnakeEnv, StepInfo, LEARNER},
+};
+
+/// Observations of every board stacked into one `[board][channel][y][x]` tensor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchObservation {
+    /// `(boards, channels, height, width)`.
+    pub shape: (usize, usize, usize, usize),
+    pub data: Vec<f32>,
+}
+
+impl BatchObservation {
+    /// The slice holding board `index`'s observation.
+    #[must_use]
+    pub fn board(&self, index: usize) -> &[f32] {
+        let (_, channels, height, width) = self.shape;
+        let size = channels * height * width;
+        &self.data[index * size..(index + 1) * size]
+    }
+}
+
+/// Results of one `VecEnv::step`, indexed by board.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchStep {
+    pub observations: BatchObservation,
+    pub rewards: Vec<f32>,
+    pub dones: Vec<bool>,
+    pub infos: Vec<StepInfo>,
+}
+
+/// Many independent `SnakeEnv`s stepped together in parallel.
+///
+/// Finished boards reset themselves straight away: their `dones` entry and info describe the
+/// episode that just ended, while their observation is already the next episode's first.
+pub struct VecEnv {
+    envs: Vec<SnakeEnv>,
+    /// Seed of each board's next episode.
+    seeds: Vec<u64>,
+}
+
+impl VecEnv {
+    /// Boards start as if `reset(0)`, so no two play the same game.
+    #[must_use]
+    pub fn new(config: EnvConfig, boards: usize) -> Self {
+        let mut batch = Self {
+            envs: (0..boards).map(|_| SnakeEnv::new(config)).collect(),
+            seeds: vec![0; boards],
+        };
+        batch.reset(0);
+        batch
+    }
+
+    /// Opponents for every board, made by `opponents(board_index)`.
+    #[must_use]
+    pub fn with_opponents(mut self, opponents: impl Fn(usize) -> Controllers) -> Self {
+        self.envs = self
+            .envs
+            .into_iter()
+            .enumerate()
+            .map(|(index, env)| env.with_opponents(opponents(index)))
+            .collect();
+        self
+    }
+
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.envs.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.envs.is_empty()
+    }
+
+    #[must_use]
+    pub fn envs(&self) -> &[SnakeEnv] {
+        &self.envs
+    }
+
+    /// Resets every board; board `i` plays seeds `seed + i`, `seed + i + len`, `seed + i + 2 * len`...
+    pub fn reset(&mut self, seed: u64) -> BatchObservation {
+        let boards = self.len() as u64;
+        self.envs
+            .par_iter_mut()
+            .zip(self.seeds.par_iter_mut())
+            .enumerate()
+            .for_each(|(index, (env, next_seed))| {
+                env.reset(seed + index as u64);
+                *next_seed = seed + index as u64 + boards;
+            });
+        self.observe()
+    }
+
+    /// Steps board `i` with `actions[i]`. Missing actions go straight.
+    pub fn step(&mut self, actions: &[Option<Direction>]) -> BatchStep {
+        let boards = self.len() as u64;
+        let results: Vec<(f32, bool, StepInfo)> = self
+            .envs
+            .par_iter_mut()
+            .zip(self.seeds.par_iter_mut())
+            .enumerate()
+            .map(|(index, (env, next_seed))| {
+                let result = env.advance(actions.get(index).copied().flatten());
+                if result.1 {
+                    env.reset(*next_seed);
+                    *next_seed += boards;
+                }
+                result
+            })
+            .collect();
+
+        let mut step = BatchStep {
+            observations: self.observe(),
+            rewards: Vec::with_capacity(results.len()),
+            dones: Vec::with_capacity(results.len()),
+            infos: Vec::with_capacity(results.len()),
+        };
+        for (reward, done, info) in results {
+            step.rewards.push(reward);
+            step.dones.push(done);
+            step.infos.push(info);
+        }
+        step
+    }
+
+    #[must_use]
+    pub fn observe(&self) -> BatchObservation {
+        let Some(first) = self.envs.first() else {
+            return BatchObservation {
+                shape: (0, 0, 0, 0),
+                data: Vec::new(),
+            };
+        };
+        let (channels, height, width) = Observation::shape(&first.config().board);
+        let size = channels * height * width;
+        let mut data = vec![0.; self.len() * size];
+        data.par_chunks_mut(size)
+            .zip(self.envs.par_iter())
+            .for_each(|(chunk, env)| Observation::encode_into(env.state(), LEARNER, chunk));
+        BatchObservation {
+            shape: (self.len(), channels, height, width),
+            data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bot::{BotController, BotLevel};
+
+    fn actions(tick: usize, boards: usize) -> Vec<Option<Direction>> {
+        (0..boards)
+            .map(|board| match (tick + board) % 5 {
+                0 => Some(Direction::Left),
+                1 => Some(Direction::Up),
+                2 => Some(Direction::Right),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn batch_matches_separate_envs() {
+        const BOARDS: usize = 8;
+        let config = EnvConfig {
+            players: 2,
+            ..EnvConfig::default()
+        };
+        let opponents = |_| Controllers::new(2).with(1, BotController(BotLevel::Greedy));
+        let mut batch = VecEnv::new(config, BOARDS).with_opponents(opponents);
+        let mut single: Vec<SnakeEnv> = (0..BOARDS)
+            .map(|index| SnakeEnv::new(config).with_opponents(opponents(index)))
+            .collect();
+
+        let observations = batch.reset(100);
+        for (index, env) in single.iter_mut().enumerate() {
+            assert_eq!(
+                observations.board(index),
+                env.reset(100 + index as u64).data
+            );
+        }
+
+        let mut episodes = [0; BOARDS];
+        for tick in 0..60 {
+            let actions = actions(tick, BOARDS);
+            let step = batch.step(&actions);
+            for (index, env) in single.iter_mut().enumerate() {
+                let (observation, reward, done, info) = env.step(actions[index]);
+                assert_eq!((step.rewards[index], step.dones[index]), (reward, done));
+                assert_eq!(step.infos[index], info);
+                if done {
+                    episodes[index] += 1;
+                    let seed = 100 + (index + episodes[index] * BOARDS) as u64;
+                    assert_eq!(step.observations.board(index), env.reset(seed).data);
+                } else {
+                    assert_eq!(step.observations.board(index), observation.data);
+                }
+            }
+        }
+        assert!(episodes.iter().any(|episodes| *episodes > 0));
+    }
+
+    #[test]
+    fn observations_are_stacked_per_board() {
+        let mut batch = VecEnv::new(EnvConfig::default(), 1000);
+
+        let step = batch.step(&[]);
+
+        assert_eq!(step.observations.shape, (1000, 5, 12, 12));
+        assert_eq!(step.observations.data.len(), 1000 * 5 * 12 * 12);
+        assert_eq!(step.dones.len(), 1000);
+        assert!(step.dones.iter().all(|done| !done));
+    }
+
+    #[test]
+    fn fresh_boards_play_different_games() {
+        let batch = VecEnv::new(EnvConfig::default(), 2);
+
+        let observations = batch.observe();
+
+        assert_ne!(observations.board(0), observations.board(1));
+        assert_eq!(batch.seeds, vec![2, 3]);
+    }
+
+    #[test]
+    fn finished_boards_start_over() {
+        let mut batch = VecEnv::new(EnvConfig::default(), 3);
+        batch.reset(0);
+
+        // Going straight up from (3, 3) hits the top wall of a 10x10 board on the seventh tick.
+        let dones: Vec<bool> = (0..7).map(|_| batch.step(&[]).dones[0]).collect();
+
+        assert_eq!(dones, vec![false, false, false, false, false, false, true]);
+        assert!(batch.envs().iter().all(|env| env.state().tick == 0));
+    }
+}